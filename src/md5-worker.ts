@@ -202,7 +202,16 @@ async function processNormalFile(
 
   const result = await calculator.calculate_md5_async(
     fileData,
-    data.md5Length || 32
+    data.md5Length || 32,
+    undefined,
+    (processed: number, total: number) => {
+      const progress = total === 0 ? 100 : (processed / total) * 100
+      self.postMessage({
+        id,
+        type: 'progress',
+        data: { progress },
+      } as WorkerMessage)
+    }
   )
 
   self.postMessage({
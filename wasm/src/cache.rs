@@ -0,0 +1,55 @@
+//! Small LRU cache of full digests for `calculate_md5_async`, keyed by a cheap pre-hash.
+
+use std::collections::VecDeque;
+
+/// Bytes from each end of the input that go into the pre-hash.
+const EDGE_BYTES: usize = 64;
+
+struct Entry {
+    key: (usize, u32),
+    /// Copy of the hashed input, compared in full on a key match so a hit can never
+    /// return another input's digest.
+    data: Vec<u8>,
+    digest: [u8; 16],
+}
+
+/// Most recently used entries first; holds at most `capacity` inputs (0 disables it).
+#[derive(Default)]
+pub struct DigestCache {
+    capacity: usize,
+    entries: VecDeque<Entry>,
+}
+
+impl DigestCache {
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.entries.truncate(capacity);
+    }
+
+    /// The cached digest of `data`, if an identical input was inserted; marks it most recent.
+    pub fn get(&mut self, data: &[u8]) -> Option<[u8; 16]> {
+        let key = pre_hash(data);
+        let index = self.entries.iter().position(|entry| entry.key == key && entry.data == data)?;
+        let entry = self.entries.remove(index)?;
+        let digest = entry.digest;
+        self.entries.push_front(entry);
+        Some(digest)
+    }
+
+    /// Remember `digest` for `data`, evicting the least recently used entry when full.
+    pub fn insert(&mut self, data: &[u8], digest: [u8; 16]) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.entries.truncate(self.capacity - 1);
+        self.entries.push_front(Entry { key: pre_hash(data), data: data.to_vec(), digest });
+    }
+}
+
+/// Length plus CRC-32 of the first and last `EDGE_BYTES` bytes: constant cost per input.
+fn pre_hash(data: &[u8]) -> (usize, u32) {
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(&data[..data.len().min(EDGE_BYTES)]);
+    crc.update(&data[data.len().saturating_sub(EDGE_BYTES)..]);
+    (data.len(), crc.finalize())
+}
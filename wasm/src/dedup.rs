@@ -0,0 +1,35 @@
+//! In-WASM duplicate detection over content digests.
+
+use md5::{Digest, Md5};
+use std::collections::HashSet;
+use wasm_bindgen::prelude::*;
+
+/// Set of MD5 digests seen so far, so a stream of chunks can be deduplicated without
+/// round-tripping every digest to JS. Chunks are compared by digest only.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct DedupIndex {
+    seen: HashSet<[u8; 16]>,
+}
+
+#[wasm_bindgen]
+impl DedupIndex {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> DedupIndex {
+        DedupIndex::default()
+    }
+
+    /// Hash `data` and record its digest; true if it had not been seen before.
+    pub fn insert_if_new(&mut self, data: &[u8]) -> bool {
+        self.seen.insert(Md5::digest(data).into())
+    }
+
+    /// Number of distinct digests recorded.
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+}
@@ -0,0 +1,51 @@
+//! Human-friendly digest fingerprints such as `able-tiger-north-42`.
+
+/// Fixed 256-entry wordlist indexed by digest bytes.
+///
+/// Fingerprints must stay stable across releases: never reorder, remove or replace entries.
+const WORDS: [&str; 256] = [
+    "able", "acid", "aged", "airy", "ajar", "alert", "alive", "amber",
+    "angry", "apple", "april", "arch", "arctic", "arrow", "ashen", "atlas",
+    "aunt", "autumn", "avid", "awake", "baker", "bald", "bamboo", "banjo",
+    "barn", "basil", "beach", "beam", "bear", "bell", "berry", "birch",
+    "bison", "black", "blade", "blaze", "bloom", "blue", "boat", "bold",
+    "bone", "brave", "brick", "bright", "brisk", "brook", "brown", "bumpy",
+    "cabin", "cactus", "calm", "camel", "candle", "canoe", "canyon", "cargo",
+    "cedar", "chalk", "cheer", "cherry", "chess", "chief", "cider", "cliff",
+    "cloud", "clover", "cobalt", "comet", "coral", "cosmic", "cotton", "crane",
+    "crisp", "crown", "cubic", "curly", "daisy", "dawn", "deep", "delta",
+    "desert", "dingo", "dizzy", "dove", "dragon", "dream", "drift", "dune",
+    "dusty", "eager", "eagle", "early", "earth", "easy", "echo", "eddy",
+    "elder", "elm", "ember", "empty", "epic", "even", "fable", "fairy",
+    "falcon", "fancy", "fern", "ferry", "fiery", "fig", "finch", "fjord",
+    "flame", "flint", "focal", "foggy", "forest", "fox", "frost", "fuzzy",
+    "gala", "gentle", "ghost", "giant", "ginger", "glad", "glass", "globe",
+    "golden", "goose", "grand", "grape", "green", "grove", "gusty", "happy",
+    "harbor", "hazel", "heron", "hidden", "hollow", "honey", "hotel", "humble",
+    "husky", "icy", "idle", "indigo", "inky", "iron", "island", "ivory",
+    "jade", "jazzy", "jelly", "jolly", "jumbo", "jungle", "karma", "kayak",
+    "kelp", "kind", "kiwi", "koala", "lake", "lemon", "level", "lilac",
+    "lime", "linen", "lively", "lofty", "lotus", "lucky", "lunar", "magic",
+    "maple", "marble", "meadow", "mellow", "melon", "merry", "metal", "misty",
+    "mocha", "moose", "mossy", "muddy", "nectar", "neon", "nimble", "noble",
+    "north", "nova", "oak", "oasis", "ocean", "olive", "onyx", "opal",
+    "orbit", "otter", "oval", "owl", "paper", "peach", "pearl", "pepper",
+    "piano", "pine", "plum", "polar", "pony", "proud", "quartz", "quick",
+    "quiet", "rabbit", "radar", "rain", "rapid", "raven", "ready", "reef",
+    "rich", "river", "robin", "rocky", "rose", "ruby", "rusty", "sable",
+    "salty", "sandy", "satin", "sea", "shady", "silent", "silver", "sleek",
+    "slim", "snowy", "solar", "sonic", "spicy", "spring", "stone", "storm",
+    "sunny", "swift", "tango", "tidal", "tiger", "timber", "topaz", "tulip",
+];
+
+/// Map a digest to `word-word-word-NN`: digest bytes 0..3 pick the words and byte 3
+/// picks the two-digit suffix.
+pub fn fingerprint(digest: &[u8]) -> String {
+    format!(
+        "{}-{}-{}-{:02}",
+        WORDS[digest[0] as usize],
+        WORDS[digest[1] as usize],
+        WORDS[digest[2] as usize],
+        digest[3] % 100
+    )
+}
@@ -1,13 +1,279 @@
+mod fingerprint;
 mod utils;
+#[cfg(feature = "session-state")]
+mod state;
+mod stream;
+mod dedup;
+mod utf8;
+mod cache;
+
+pub use dedup::DedupIndex;
+pub use stream::Md5Stream;
 
 use wasm_bindgen::prelude::*;
 use md5::{Md5, Digest};
-use std::collections::HashMap;
-use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::thread::LocalKey;
+use std::io::Read;
+
+/// Bytes processed between cooperative yields in the async hashing loops.
+const YIELD_INTERVAL: usize = 2 * 1024 * 1024;
+
+/// `BIT_REVERSED[b]` is `b` with its bit order reversed (bit 0 becomes bit 7).
+const BIT_REVERSED: [u8; 256] = {
+    let mut table = [0u8; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = (i as u8).reverse_bits();
+        i += 1;
+    }
+    table
+};
+
+/// How many recently finalized session ids are remembered for `finalize_incremental_md5_strict`.
+const FINALIZED_ID_CAPACITY: usize = 256;
+
+/// RFC 1321 test suite inputs and digests, checked by `self_test_async`.
+const SELF_TEST_VECTORS: [(&[u8], &str); 7] = [
+    (b"", "d41d8cd98f00b204e9800998ecf8427e"),
+    (b"a", "0cc175b9c0f1b6a831c399e269772661"),
+    (b"abc", "900150983cd24fb0d6963f7d28e17f72"),
+    (b"message digest", "f96b697d7cb7938d525a2f31aaf161d0"),
+    (b"abcdefghijklmnopqrstuvwxyz", "c3fcd3d76192e4007dfb496cca67e13b"),
+    (b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789", "d174ab98d277d9f5a5611c2c9f419d9f"),
+    (
+        b"12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+        "57edf4a22be3c955ac49da2e2107b67a",
+    ),
+];
+
+/// Default limit on live incremental sessions; see `set_max_sessions`.
+const DEFAULT_MAX_SESSIONS: usize = 1024;
 
 // WASM is single-threaded; thread_local + RefCell avoids unnecessary Mutex overhead.
 thread_local! {
-    static HASH_STATES: RefCell<HashMap<String, Md5>> = RefCell::new(HashMap::new());
+    static HASH_STATES: RefCell<HashMap<String, Session>> = RefCell::new(HashMap::new());
+    static ROLLING_STATES: RefCell<HashMap<String, RollingWindow>> = RefCell::new(HashMap::new());
+    /// Most recently finalized session ids, oldest first; an id leaves when it is restarted.
+    static FINALIZED_IDS: RefCell<VecDeque<String>> = const { RefCell::new(VecDeque::new()) };
+    /// Owner token and hasher of each incremental SHA-256 session.
+    #[cfg(feature = "sha256")]
+    static SHA256_STATES: RefCell<HashMap<String, (u32, sha2::Sha256)>> = RefCell::new(HashMap::new());
+    /// Source of `Md5Calculator` owner tokens.
+    static NEXT_OWNER: Cell<u32> = const { Cell::new(0) };
+}
+
+/// A JS callback and its arguments, invoked once the sessions map is no longer borrowed.
+type DeferredCall = (js_sys::Function, js_sys::Array);
+
+/// Bytes hashed by one session update, the callbacks it made due, and the hex digest if it
+/// filled a capped session.
+type AppliedUpdate = (usize, Vec<DeferredCall>, Option<String>);
+
+/// Invoke deferred session callbacks. Exceptions thrown by callbacks are ignored so they
+/// cannot affect the hashing result.
+fn fire_callbacks(calls: Vec<DeferredCall>) {
+    for (callback, args) in calls {
+        let _ = callback.apply(&JsValue::NULL, &args);
+    }
+}
+
+/// A sliding-window digest over the most recent `capacity` bytes pushed.
+struct RollingWindow {
+    /// Token of the calculator that started the session; see `Md5Calculator::owner`.
+    owner: u32,
+    capacity: usize,
+    bytes: VecDeque<u8>,
+    /// Hash of exactly `bytes`; rebuilt whenever old bytes are evicted.
+    hasher: Md5,
+}
+
+impl RollingWindow {
+    fn push(&mut self, data: &[u8]) {
+        let data = &data[data.len().saturating_sub(self.capacity)..];
+        let overflow = (self.bytes.len() + data.len()).saturating_sub(self.capacity);
+        self.bytes.extend(data);
+
+        if overflow == 0 {
+            self.hasher.update(data);
+            return;
+        }
+
+        // MD5 cannot "un-hash" a prefix, so re-hash the retained window from scratch.
+        self.bytes.drain(..overflow);
+        let (front, back) = self.bytes.as_slices();
+        self.hasher = Md5::new();
+        self.hasher.update(front);
+        self.hasher.update(back);
+    }
+}
+
+/// Hasher behind incremental sessions. The `session-state` feature swaps in the
+/// transparent implementation so sessions can be inspected, exported and imported.
+#[cfg(not(feature = "session-state"))]
+type SessionHasher = Md5;
+#[cfg(feature = "session-state")]
+type SessionHasher = state::Md5State;
+
+/// An incremental session: the running hasher plus any per-session bookkeeping.
+struct Session {
+    /// Token of the calculator that last started the session; see `Md5Calculator::owner`.
+    owner: u32,
+    hasher: SessionHasher,
+    /// Opaque caller-provided value, returned as-is by `get_session_meta`.
+    meta: JsValue,
+    /// Bytes fed through `update_incremental_md5` (plus those restored by
+    /// `import_incremental_md5`) and the time spent hashing them. Checkpoints, interval
+    /// digests and byte caps all count against this total.
+    update_bytes: u64,
+    update_ms: f64,
+    /// Length of each `update_incremental_md5` call, recorded only while
+    /// `set_track_update_sizes` is on.
+    update_sizes: Vec<usize>,
+    #[cfg(feature = "session-state")]
+    checkpoint: Option<ByteInterval>,
+    interval_digest: Option<ByteInterval>,
+    cap: Option<ByteCap>,
+    /// Full hex digest and `update_bytes` at the last `commit_incremental_md5`.
+    last_commit: Option<(String, u64)>,
+    /// Index `update_incremental_md5_at` applies next, and the later chunks buffered until
+    /// the run up to them is complete.
+    next_chunk: u64,
+    pending_chunks: BTreeMap<u64, Vec<u8>>,
+}
+
+/// Byte limit of a `start_incremental_md5_capped` session and the callback run when the
+/// session auto-finalizes on reaching it.
+struct ByteCap {
+    max_bytes: u64,
+    on_complete: js_sys::Function,
+}
+
+/// A callback due each time a session's byte count crosses a multiple of `every_bytes`:
+/// periodic state export for crash recovery, or running digests for progress displays.
+struct ByteInterval {
+    every_bytes: u64,
+    next_at: u64,
+    callback: js_sys::Function,
+}
+
+impl ByteInterval {
+    fn new(every_bytes: u64, processed: u64, callback: js_sys::Function) -> ByteInterval {
+        ByteInterval {
+            every_bytes,
+            next_at: (processed / every_bytes + 1) * every_bytes,
+            callback,
+        }
+    }
+
+    /// Whether `processed` has reached the next boundary; if so, move past it (once, even if
+    /// several multiples were crossed).
+    fn advance(&mut self, processed: u64) -> bool {
+        if processed < self.next_at {
+            return false;
+        }
+        self.next_at = (processed / self.every_bytes + 1) * self.every_bytes;
+        true
+    }
+}
+
+impl Session {
+    fn new() -> Session {
+        Session {
+            owner: 0,
+            hasher: SessionHasher::new(),
+            meta: JsValue::UNDEFINED,
+            update_bytes: 0,
+            update_ms: 0.0,
+            update_sizes: Vec::new(),
+            #[cfg(feature = "session-state")]
+            checkpoint: None,
+            interval_digest: None,
+            cap: None,
+            last_commit: None,
+            next_chunk: 0,
+            pending_chunks: BTreeMap::new(),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.hasher.update(data);
+    }
+
+    /// If the last update crossed an interval-digest boundary, return the callback with
+    /// `(bytes_processed, hex)`, for the caller to fire once the sessions borrow is released.
+    fn due_interval_digest(&mut self) -> Option<DeferredCall> {
+        let processed = self.update_bytes;
+        let interval = self.interval_digest.as_mut()?;
+        if !interval.advance(processed) {
+            return None;
+        }
+        let hex = format!("{:x}", self.hasher.clone().finalize());
+        let args = js_sys::Array::of2(&JsValue::from(processed as f64), &JsValue::from_str(&hex));
+        Some((interval.callback.clone(), args))
+    }
+}
+
+/// Shared state behind an `md5_tee` stream: the source reader, the running hash (taken once
+/// the digest promise settles) and that promise's resolve/reject functions.
+struct Tee {
+    reader: web_sys::ReadableStreamDefaultReader,
+    hasher: RefCell<Option<Md5>>,
+    /// The owning calculator's `total_bytes_hashed` counter.
+    bytes_hashed: Rc<Cell<u64>>,
+    resolve: js_sys::Function,
+    reject: js_sys::Function,
+}
+
+impl Tee {
+    /// Forward one source chunk to `controller`, hashing it on the way through.
+    async fn pull(&self, controller: &web_sys::ReadableStreamDefaultController) {
+        let step = match wasm_bindgen_futures::JsFuture::from(self.reader.read()).await {
+            Ok(result) => Self::chunk_of(&result),
+            Err(error) => Err(error),
+        };
+        match step {
+            Ok(Some(chunk)) => {
+                if let Some(hasher) = self.hasher.borrow_mut().as_mut() {
+                    hasher.update(chunk.to_vec());
+                    self.bytes_hashed.set(self.bytes_hashed.get().saturating_add(u64::from(chunk.length())));
+                }
+                let _ = controller.enqueue_with_chunk(&chunk);
+            }
+            Ok(None) => {
+                let _ = controller.close();
+                if let Some(hasher) = self.hasher.borrow_mut().take() {
+                    let hex = format!("{:x}", hasher.finalize());
+                    let _ = self.resolve.call1(&JsValue::NULL, &JsValue::from_str(&hex));
+                }
+            }
+            Err(error) => {
+                let _ = self.reader.cancel_with_reason(&error);
+                controller.error_with_e(&error);
+                self.fail(&error);
+            }
+        }
+    }
+
+    /// `Some(chunk)` for a data read result, `None` once the source is done.
+    fn chunk_of(result: &JsValue) -> Result<Option<js_sys::Uint8Array>, JsValue> {
+        if js_sys::Reflect::get(result, &JsValue::from_str("done"))?.is_truthy() {
+            return Ok(None);
+        }
+        js_sys::Reflect::get(result, &JsValue::from_str("value"))?
+            .dyn_into::<js_sys::Uint8Array>()
+            .map(Some)
+            .map_err(|_| js_error("ReadableStream yielded a non-Uint8Array chunk"))
+    }
+
+    /// Reject the digest promise, unless it has already settled.
+    fn fail(&self, reason: &JsValue) {
+        if self.hasher.borrow_mut().take().is_some() {
+            let _ = self.reject.call1(&JsValue::NULL, reason);
+        }
+    }
 }
 
 #[wasm_bindgen]
@@ -24,9 +290,356 @@ macro_rules! console_log {
     }
 }
 
+/// Suggest a worker/task count for hashing `data_len` bytes on this device.
+/// Uses `navigator.hardwareConcurrency` (window or worker scope, falling back to 4)
+/// and allots roughly 8 MB per task; the result is clamped to 1..=16.
+#[wasm_bindgen]
+pub fn recommended_task_count(data_len: usize) -> usize {
+    const BYTES_PER_TASK: usize = 8 * 1024 * 1024;
+    const MAX_TASKS: usize = 16;
+
+    let cores = hardware_concurrency().unwrap_or(4);
+    let by_size = data_len.div_ceil(BYTES_PER_TASK);
+    cores.min(by_size).clamp(1, MAX_TASKS)
+}
+
+fn hardware_concurrency() -> Option<usize> {
+    let cores = if let Some(window) = web_sys::window() {
+        window.navigator().hardware_concurrency()
+    } else {
+        js_sys::global()
+            .dyn_into::<web_sys::WorkerGlobalScope>()
+            .ok()?
+            .navigator()
+            .hardware_concurrency()
+    };
+    if cores >= 1.0 {
+        Some(cores as usize)
+    } else {
+        None
+    }
+}
+
+/// Whether `s` is a well-formed full MD5: exactly 32 hex characters of either case,
+/// with no surrounding whitespace.
+#[wasm_bindgen]
+pub fn is_valid_md5_hex(s: &str) -> bool {
+    s.len() == 32 && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Canonical form of a user-provided MD5: trimmed and lowercased, or `undefined` when the
+/// trimmed value is not 32 hex characters.
+#[wasm_bindgen]
+pub fn normalize_md5_hex(s: &str) -> Option<String> {
+    let trimmed = s.trim();
+    if is_valid_md5_hex(trimmed) {
+        Some(trimmed.to_ascii_lowercase())
+    } else {
+        None
+    }
+}
+
+/// Decode a hex string (either case) into bytes. Throws on odd length or non-hex chars.
+#[wasm_bindgen]
+pub fn hex_to_bytes(s: &str) -> Result<Vec<u8>, JsValue> {
+    decode_hex(s).ok_or_else(|| js_error(&format!("Invalid hex string: {}", s)))
+}
+
+/// Encode bytes as hex, lowercase unless `uppercase` is set.
+#[wasm_bindgen]
+pub fn bytes_to_hex(b: &[u8], uppercase: bool) -> String {
+    b.iter()
+        .map(|byte| if uppercase { format!("{:02X}", byte) } else { format!("{:02x}", byte) })
+        .collect()
+}
+
+/// Whether this code is running inside a Web Worker (dedicated, shared or service).
+#[wasm_bindgen]
+pub fn is_worker_context() -> bool {
+    js_sys::global().dyn_into::<web_sys::WorkerGlobalScope>().is_ok()
+}
+
+/// Worker-side entrypoint: hash `file` in `chunk_size` slices from inside a dedicated
+/// worker, posting `{ type: "progress", bytesProcessed, totalBytes }` after each slice and
+/// `{ type: "done", hex }` at the end. Resolves with the hex as well. Rejects outside a
+/// dedicated worker, for a zero `chunk_size`, or when a slice cannot be read.
+#[wasm_bindgen]
+pub async fn hash_in_worker(file: web_sys::File, chunk_size: usize, md5_length: usize) -> Result<String, JsValue> {
+    check_md5_length(md5_length)?;
+    let scope = js_sys::global()
+        .dyn_into::<web_sys::DedicatedWorkerGlobalScope>()
+        .map_err(|_| js_error("hash_in_worker must be called from a dedicated Web Worker"))?;
+    if chunk_size == 0 {
+        return Err(js_error("chunk_size must be greater than zero"));
+    }
+
+    let total = file.size();
+    let mut hasher = Md5::new();
+    let mut offset = 0.0;
+
+    while offset < total {
+        let end = total.min(offset + chunk_size as f64);
+        hasher.update(read_blob_range(&file, offset, end).await?);
+        offset = end;
+
+        scope.post_message(&js_object(&[
+            ("type", JsValue::from_str("progress")),
+            ("bytesProcessed", JsValue::from_f64(offset)),
+            ("totalBytes", JsValue::from_f64(total)),
+        ]))?;
+    }
+
+    let hex = Md5Calculator::truncate_hash(&format!("{:x}", hasher.finalize()), md5_length);
+    scope.post_message(&js_object(&[
+        ("type", JsValue::from_str("done")),
+        ("hex", JsValue::from_str(&hex)),
+    ]))?;
+    Ok(hex)
+}
+
+/// Hash a small fixed buffer once so the engine compiles and tiers up the MD5 and hex paths
+/// before the first real hash. Touches no session or calculator state; safe to call again.
+#[wasm_bindgen]
+pub fn warmup() {
+    let block = [0x5au8; 4 * 64];
+    let digest = Md5::digest(std::hint::black_box(&block[..]));
+    #[cfg(feature = "session-state")]
+    {
+        let mut state = SessionHasher::new();
+        state.update(&block);
+        std::hint::black_box(state.finalize());
+    }
+    std::hint::black_box(format!("{:x}", digest));
+}
+
+/// Conservative estimate of the largest buffer `calculate_md5_async` can take right now
+/// without running out of memory. The input is copied into linear memory, which can only
+/// grow up to an engine limit (assumed 2 GiB, the lowest common ceiling), so this is that
+/// limit minus the memory already in use and 1 MiB of headroom for the hasher and
+/// bookkeeping. Free space inside the current memory is not counted. Recomputed per call.
+#[wasm_bindgen]
+pub fn max_single_buffer_len() -> usize {
+    const ASSUMED_MEMORY_LIMIT: usize = 2 * 1024 * 1024 * 1024;
+    const HEADROOM: usize = 1024 * 1024;
+    ASSUMED_MEMORY_LIMIT.saturating_sub(linear_memory_bytes()).saturating_sub(HEADROOM)
+}
+
+/// Which web APIs this module relies on are available in the current runtime:
+/// `{ blob, readable_stream, performance_now, shared_array_buffer, scheduler_post_task }`.
+/// Lets callers pick a code path up front instead of hitting an exception inside WASM.
+#[wasm_bindgen]
+pub fn feature_support() -> JsValue {
+    let global = JsValue::from(js_sys::global());
+    let lookup = |target: &JsValue, name: &str| js_sys::Reflect::get(target, &JsValue::from_str(name)).unwrap_or(JsValue::UNDEFINED);
+    let is_function = |target: &JsValue, name: &str| lookup(target, name).is_function();
+
+    js_object(&[
+        ("blob", JsValue::from_bool(is_function(&global, "Blob"))),
+        ("readable_stream", JsValue::from_bool(is_function(&global, "ReadableStream"))),
+        ("performance_now", JsValue::from_bool(is_function(&lookup(&global, "performance"), "now"))),
+        ("shared_array_buffer", JsValue::from_bool(is_function(&global, "SharedArrayBuffer"))),
+        ("scheduler_post_task", JsValue::from_bool(is_function(&lookup(&global, "scheduler"), "postTask"))),
+    ])
+}
+
+/// What this build can compute: `{ algorithms, formats, features }`, each an array of names.
+/// `formats` lists the `DigestFormat` renderings (from `DIGEST_FORMATS`) followed by the
+/// `ENCODED_FORMATS`; `features` lists the optional cargo features compiled in.
+#[wasm_bindgen]
+pub fn capabilities() -> JsValue {
+    let algorithms: &[&str] = &[
+        "md5",
+        "crc32",
+        #[cfg(feature = "sha256")]
+        "sha256",
+    ];
+    let features: &[&str] = &[
+        #[cfg(feature = "session-state")]
+        "session-state",
+        #[cfg(feature = "sha256")]
+        "sha256",
+    ];
+
+    let names = |names: &[&str]| JsValue::from(names.iter().map(|name| JsValue::from_str(name)).collect::<js_sys::Array>());
+    js_object(&[
+        ("algorithms", names(algorithms)),
+        ("formats", names(&DIGEST_FORMATS.iter().map(|&(_, name, _)| name).chain(ENCODED_FORMATS.iter().copied()).collect::<Vec<_>>())),
+        ("features", names(features)),
+    ])
+}
+
+/// Whether two files have the same content: sizes are compared first and differing sizes
+/// resolve `false` without reading either file; otherwise both are hashed `chunk_size`
+/// bytes at a time and their full digests compared. Read errors and a zero `chunk_size` reject.
+#[wasm_bindgen]
+pub async fn files_identical_async(a: web_sys::File, b: web_sys::File, chunk_size: usize) -> Result<bool, JsValue> {
+    if chunk_size == 0 {
+        return Err(js_error("chunk_size must be greater than zero"));
+    }
+    if a.size() != b.size() {
+        return Ok(false);
+    }
+    Ok(digest_blob(&a, chunk_size).await? == digest_blob(&b, chunk_size).await?)
+}
+
+/// Wait for an IndexedDB request to finish: resolves with its `success` event or rejects
+/// with the request's `error` (falling back to the `error` event).
+async fn idb_request_done(request: &web_sys::IdbRequest) -> Result<JsValue, JsValue> {
+    let done = js_sys::Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+    });
+    let outcome = wasm_bindgen_futures::JsFuture::from(done).await;
+    request.set_onsuccess(None);
+    request.set_onerror(None);
+    outcome.map_err(|event| {
+        js_sys::Reflect::get(request, &JsValue::from_str("error"))
+            .ok()
+            .filter(|error| !error.is_null() && !error.is_undefined())
+            .unwrap_or(event)
+    })
+}
+
+/// Read `blob[start..end]` into WASM memory.
+async fn read_blob_range(blob: &web_sys::Blob, start: f64, end: f64) -> Result<Vec<u8>, JsValue> {
+    let slice = blob.slice_with_f64_and_f64(start, end)?;
+    let buffer = wasm_bindgen_futures::JsFuture::from(slice.array_buffer()).await?;
+    Ok(js_sys::Uint8Array::new(&buffer).to_vec())
+}
+
+/// Full MD5 of `blob`, read `chunk_size` bytes at a time.
+async fn digest_blob(blob: &web_sys::Blob, chunk_size: usize) -> Result<md5::digest::Output<Md5>, JsValue> {
+    let total = blob.size();
+    let mut hasher = Md5::new();
+    let mut offset = 0.0;
+    while offset < total {
+        let end = total.min(offset + chunk_size as f64);
+        hasher.update(read_blob_range(blob, offset, end).await?);
+        offset = end;
+    }
+    Ok(hasher.finalize())
+}
+
+/// Which end of the hex digest a truncated result keeps.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TruncateSide {
+    /// Leading characters, as `md5_length` does everywhere else.
+    Start,
+    /// Trailing characters, e.g. the last 8 hex chars for legacy identifiers.
+    End,
+}
+
+/// How a calculator treats `md5_length` values that are not a standard digest size.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LengthRounding {
+    /// Return exactly `md5_length` hex chars (the default, for compatibility).
+    Exact,
+    /// Lengths 17..=31 become 16 (8 bytes); other odd lengths drop to the previous even length.
+    RoundDownToByte,
+    /// Lengths 17..=31 become 32 (16 bytes); other odd lengths rise to the next even length.
+    RoundUpToByte,
+}
+
+/// Byte order used when digest bytes are read as integers. Big-endian matches Python's
+/// `int(hexdigest, 16)` and is the default wherever a method does not take one.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endian {
+    /// Most significant byte first.
+    Big,
+    /// Least significant byte first.
+    Little,
+}
+
+/// Display format for `calculate_md5_formatted_async`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DigestFormat {
+    /// Lowercase hex, as returned everywhere else.
+    Hex,
+    /// Uppercase hex.
+    HexUpper,
+    /// Lowercase hex byte pairs separated by colons, e.g. `ab:cd:ef`.
+    HexColon,
+    /// Uppercase hex byte pairs separated by colons, e.g. `AB:CD:EF`.
+    HexColonUpper,
+}
+
+/// Turns truncated lowercase hex into one `DigestFormat` rendering.
+type FormatRenderer = fn(String) -> String;
+
+/// Every `DigestFormat` with its `capabilities()` name and how `calculate_md5_formatted_async`
+/// renders the truncated hex in it.
+const DIGEST_FORMATS: &[(DigestFormat, &str, FormatRenderer)] = &[
+    (DigestFormat::Hex, "hex", |hex| hex),
+    (DigestFormat::HexUpper, "hex_upper", |hex| hex.to_ascii_uppercase()),
+    (DigestFormat::HexColon, "hex_colon", colon_separated),
+    (DigestFormat::HexColonUpper, "hex_colon_upper", |hex| colon_separated(hex).to_ascii_uppercase()),
+];
+
+/// Digest encodings with a dedicated method rather than a `DigestFormat`: `base64`
+/// (`calculate_md5_base64_async`), `bytes` (the raw digest, `calculate_md5_bytes_async`),
+/// `etag`, `fingerprint`, `sortkey` and `uuid_v3`.
+const ENCODED_FORMATS: &[&str] = &["base64", "bytes", "etag", "fingerprint", "sortkey", "uuid_v3"];
+
+/// Hex byte pairs joined by colons; an odd-length input ends in a single hex char.
+fn colon_separated(hex: String) -> String {
+    hex.as_bytes().chunks(2).map(String::from_utf8_lossy).collect::<Vec<_>>().join(":")
+}
+
 #[wasm_bindgen]
 pub struct Md5Calculator {
     enable_log: bool,
+    max_block_ms: f64,
+    /// Fixed chunk size set via `set_chunk_size`; `None` picks 128KB/256KB by input size.
+    chunk_size: Option<usize>,
+    yield_interval: usize,
+    strict_empty_updates: bool,
+    track_update_sizes: bool,
+    yielding_enabled: bool,
+    call_counter: Cell<u32>,
+    paused: Cell<bool>,
+    resume_waiters: RefCell<Vec<js_sys::Function>>,
+    last_peak_memory: Cell<usize>,
+    /// `(bytes, elapsed_ms)` of the most recent `calculate_md5_async` call.
+    last_stats: Cell<(usize, f64)>,
+    scheduler_priority: Option<String>,
+    /// Unique per instance and stamped on every session it starts. The last instance to start
+    /// an id owns it, so `reset` and dropping only remove sessions still carrying this token.
+    owner: u32,
+    on_finalize: RefCell<Option<js_sys::Function>>,
+    length_rounding: LengthRounding,
+    read_retries: usize,
+    max_sessions: usize,
+    /// Bytes hashed by this instance since construction; never reset.
+    bytes_hashed: Rc<Cell<u64>>,
+    digest_cache: RefCell<cache::DigestCache>,
+    /// Instance-scoped stream for `update` / `digest`, outside the global session map.
+    hasher: Md5,
+}
+
+/// Freeing a calculator from JS (`.free()`) drops the sessions it started, as `reset` does.
+impl Drop for Md5Calculator {
+    fn drop(&mut self) {
+        self.drop_owned_sessions();
+    }
+}
+
+/// Options accepted by `Md5Calculator.with_options`; every field is optional.
+#[derive(serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct CalculatorOptions {
+    enable_log: bool,
+    chunk_size: Option<usize>,
+    yield_interval: Option<usize>,
+}
+
+impl Default for Md5Calculator {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[wasm_bindgen]
@@ -36,128 +649,3455 @@ impl Md5Calculator {
         utils::set_panic_hook();
         Md5Calculator {
             enable_log: false,
+            max_block_ms: 0.0,
+            chunk_size: None,
+            yield_interval: YIELD_INTERVAL,
+            strict_empty_updates: false,
+            track_update_sizes: false,
+            yielding_enabled: true,
+            call_counter: Cell::new(0),
+            paused: Cell::new(false),
+            resume_waiters: RefCell::new(Vec::new()),
+            last_peak_memory: Cell::new(0),
+            last_stats: Cell::new((0, 0.0)),
+            scheduler_priority: None,
+            owner: NEXT_OWNER.with(|next| {
+                let owner = next.get().wrapping_add(1);
+                next.set(owner);
+                owner
+            }),
+            on_finalize: RefCell::new(None),
+            length_rounding: LengthRounding::Exact,
+            read_retries: 0,
+            max_sessions: DEFAULT_MAX_SESSIONS,
+            bytes_hashed: Rc::new(Cell::new(0)),
+            digest_cache: RefCell::new(cache::DigestCache::default()),
+            hasher: Md5::new(),
+        }
+    }
+
+    /// Construct from an options object `{ enableLog, chunkSize, yieldInterval }`, all
+    /// optional; `undefined`, `null` or `{}` give the same calculator as `new()`. Unknown keys
+    /// are ignored, including the `taskCount` of the worker-pool constructor in `src` (this
+    /// calculator always hashes on the calling thread). Throws for a malformed object or a
+    /// zero `chunkSize`/`yieldInterval`.
+    #[wasm_bindgen]
+    pub fn with_options(options: JsValue) -> Result<Md5Calculator, JsValue> {
+        let options: CalculatorOptions = if options.is_undefined() || options.is_null() {
+            CalculatorOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options).map_err(|e| js_error(&format!("Invalid calculator options: {}", e)))?
+        };
+
+        let mut calculator = Md5Calculator::new();
+        calculator.set_log_enabled(options.enable_log);
+        if let Some(chunk_size) = options.chunk_size {
+            calculator.set_chunk_size(chunk_size)?;
+        }
+        if let Some(yield_interval) = options.yield_interval {
+            calculator.set_yield_interval(yield_interval)?;
         }
+        Ok(calculator)
     }
 
     /// Compute MD5 asynchronously, yielding control periodically for large inputs.
-    /// `md5_length`: 16 returns the first 16 hex chars, 32 returns the full hash.
+    /// `md5_length` is the number of leading hex chars to return (after the length-rounding
+    /// policy): 16 and 32 are the usual choices; it must be between 1 and 32, otherwise the
+    /// call rejects before hashing. Empty input is hashed like any other, giving the MD5 of
+    /// zero bytes (`d41d8cd98f00b204e9800998ecf8427e`), never an empty string.
+    /// `call_id` (optional) prefixes this call's log lines as `[call <id>]` so concurrent
+    /// calls can be told apart; one is generated when logging is enabled and none is given.
+    /// `on_progress` (optional) is called as `(bytes_processed, total_bytes)` at every yield
+    /// point before the end (so at least once per yield interval, 2 MB by default) and exactly once at completion with
+    /// `bytes_processed == total_bytes`. Exceptions it throws are ignored.
+    /// `signal` (optional) cancels the call: it is checked before hashing and at every yield
+    /// point, and once it has aborted the partial hash is dropped and the call resolves with
+    /// an empty string (no completion progress is reported).
     #[wasm_bindgen]
-    pub async fn calculate_md5_async(&self, data: &[u8], md5_length: usize) -> String {
+    pub async fn calculate_md5_async(
+        &self,
+        data: &[u8],
+        md5_length: usize,
+        call_id: Option<String>,
+        on_progress: Option<js_sys::Function>,
+        signal: Option<web_sys::AbortSignal>,
+    ) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
         let data_len = data.len();
+        let call_id = if self.enable_log {
+            call_id.unwrap_or_else(|| self.next_call_id())
+        } else {
+            String::new()
+        };
 
-        console_log!(self.enable_log, "Starting async MD5 calculation, data length: {}", data_len);
+        console_log!(self.enable_log, "[call {}] Starting async MD5 calculation, data length: {}", call_id, data_len);
+        self.last_stats.set((0, 0.0));
+        let started = now_ms();
 
-        let mut hasher = Md5::new();
+        if signal.as_ref().is_some_and(|signal| signal.aborted()) {
+            console_log!(self.enable_log, "[call {}] Aborted before hashing", call_id);
+            return Ok(String::new());
+        }
 
-        if data_len > 512 * 1024 {
-            // Large inputs: chunk and yield every 2 MB to keep the UI responsive.
-            let chunk_size = if data_len > 10 * 1024 * 1024 {
-                256 * 1024
-            } else {
-                128 * 1024
-            };
+        let cached = self.digest_cache.borrow_mut().get(data);
+        let hash = match cached {
+            Some(digest) => {
+                console_log!(self.enable_log, "[call {}] Digest cache hit", call_id);
+                digest
+            }
+            None => {
+                let mut hasher = Md5::new();
+                if !self.feed_chunked_with(data, |chunk| hasher.update(chunk), on_progress.as_ref(), signal.as_ref()).await {
+                    console_log!(self.enable_log, "[call {}] Aborted by signal", call_id);
+                    return Ok(String::new());
+                }
+                let digest: [u8; 16] = hasher.finalize().into();
+                self.digest_cache.borrow_mut().insert(data, digest);
+                digest
+            }
+        };
+        if let Some(on_progress) = &on_progress {
+            report_progress(on_progress, data_len, data_len);
+        }
+        let hash_string = format!("{:x}", md5::digest::Output::<Md5>::from(hash));
 
-            let yield_interval = 2 * 1024 * 1024;
-            let mut bytes_since_yield = 0usize;
+        let truncated_hash = self.truncate(&hash_string, md5_length);
+        self.last_peak_memory.set(linear_memory_bytes());
+        let elapsed_ms = now_ms() - started;
+        self.last_stats.set((data_len, elapsed_ms));
 
-            for chunk in data.chunks(chunk_size) {
-                hasher.update(chunk);
-                bytes_since_yield += chunk.len();
+        if self.enable_log {
+            let mbps = mb_per_second(data_len, elapsed_ms);
+            log(&format!(
+                "[call {}] Async MD5 calculation completed: {}, {} bytes in {:.1} ms ({:.1} MB/s)",
+                call_id, truncated_hash, data_len, elapsed_ms, mbps
+            ));
+        }
+        Ok(truncated_hash)
+    }
 
-                if bytes_since_yield >= yield_interval {
-                    bytes_since_yield = 0;
-                    wasm_bindgen_futures::JsFuture::from(
-                        js_sys::Promise::resolve(&JsValue::NULL)
-                    ).await.unwrap();
-                }
-            }
-        } else {
-            hasher.update(data);
+    /// `calculate_md5_async` over the bytes of an `ArrayBuffer`, e.g. straight from
+    /// `FileReader` or `Response.arrayBuffer()`, without wrapping it in a `Uint8Array` first.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_array_buffer_async(&self, buffer: js_sys::ArrayBuffer, md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        let data = js_sys::Uint8Array::new(&buffer).to_vec();
+        self.calculate_md5_async(&data, md5_length, None, None, None).await
+    }
+
+    /// Hash a `Uint8Array` without first copying all of it into WASM memory: the array is read
+    /// one chunk at a time into a single reused buffer, so the extra memory needed stays at one
+    /// chunk (the `set_chunk_size` value, 256KB by default) whatever the input size. The call
+    /// keeps no reference to `data` after it resolves, so JS can release the array at once.
+    /// Yields, reports `on_progress`, honours `signal` and records `last_stats` and
+    /// `last_hash_peak_memory_bytes` like `calculate_md5_async`.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_from_js_async(
+        &self,
+        data: js_sys::Uint8Array,
+        md5_length: usize,
+        on_progress: Option<js_sys::Function>,
+        signal: Option<web_sys::AbortSignal>,
+    ) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        self.last_stats.set((0, 0.0));
+        if signal.as_ref().is_some_and(|signal| signal.aborted()) {
+            return Ok(String::new());
         }
+        let started = now_ms();
+        let total = data.length() as usize;
+        let mut buffer = vec![0u8; self.chunk_size.unwrap_or(256 * 1024).min(total)];
+        let mut hasher = Md5::new();
 
-        let hash = hasher.finalize();
-        let hash_string = format!("{:x}", hash);
+        let sink = |range: std::ops::Range<usize>| {
+            let mut offset = range.start;
+            while offset < range.end {
+                let end = range.end.min(offset + buffer.len());
+                let chunk = &mut buffer[..end - offset];
+                data.subarray(offset as u32, end as u32).copy_to(chunk);
+                hasher.update(&chunk[..]);
+                offset = end;
+            }
+        };
+        let completed = self.feed_ranges(total, sink, on_progress.as_ref(), signal.as_ref()).await;
+        drop(data);
+        if !completed {
+            return Ok(String::new());
+        }
+        if let Some(on_progress) = &on_progress {
+            report_progress(on_progress, total, total);
+        }
 
-        let truncated_hash = Self::truncate_hash(&hash_string, md5_length);
+        let truncated_hash = self.truncate(&format!("{:x}", hasher.finalize()), md5_length);
+        self.last_peak_memory.set(linear_memory_bytes());
+        self.last_stats.set((total, now_ms() - started));
+        console_log!(self.enable_log, "Chunk-copied MD5 calculation completed: {}, data length: {}", truncated_hash, total);
+        Ok(truncated_hash)
+    }
 
-        console_log!(self.enable_log, "Async MD5 calculation completed: {}", truncated_hash);
-        truncated_hash
+    /// `calculate_md5_async` with details: `{ hex, byteLength, truncatedLength, durationMs }`,
+    /// where `truncatedLength` is `hex.length` (so an empty input still gives a 32-char `hex`
+    /// and is told apart from a failure) and `durationMs` is wall-clock time from
+    /// `Date.now()` around the hashing, including yields.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_detailed_async(&self, data: &[u8], md5_length: usize) -> Result<JsValue, JsValue> {
+        check_md5_length(md5_length)?;
+        let started = js_sys::Date::now();
+        let hex = self.calculate_md5_async(data, md5_length, None, None, None).await?;
+        let duration_ms = js_sys::Date::now() - started;
+        Ok(js_object(&[
+            ("hex", JsValue::from_str(&hex)),
+            ("byteLength", JsValue::from(data.len() as f64)),
+            ("truncatedLength", JsValue::from(hex.len() as f64)),
+            ("durationMs", JsValue::from(duration_ms)),
+        ]))
     }
 
+    /// Hash `data` in one update, with no chunk split and no yields whatever the chunk and
+    /// yield settings. Same digest, truncation and logging as `calculate_md5_async`; meant
+    /// for small inputs where yielding only adds latency.
     #[wasm_bindgen]
-    pub fn set_log_enabled(&mut self, enable: bool) {
-        self.enable_log = enable;
+    pub async fn calculate_md5_single_async(&self, data: &[u8], md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        self.count_hashed(data.len() as u64);
+        let truncated_hash = self.truncate(&format!("{:x}", Md5::digest(data)), md5_length);
+        console_log!(self.enable_log, "Single-pass MD5 calculation completed: {}, data length: {}", truncated_hash, data.len());
+        Ok(truncated_hash)
     }
 
+    /// Safe entrypoint: resolves with `{ ok: true, hex }`, or rejects with `{ ok: false, error }`
+    /// (never throws or panics). `md5_length` must be between 1 and 32. `calculate_md5_async`
+    /// remains the raw string variant.
     #[wasm_bindgen]
-    pub fn is_log_enabled(&self) -> bool {
-        self.enable_log
+    pub async fn calculate_md5(&self, data: &[u8], md5_length: usize) -> Result<JsValue, JsValue> {
+        if let Err(error) = check_md5_length(md5_length) {
+            let error = js_sys::Error::from(error).message();
+            console_log!(self.enable_log, "WARNING: {}", error);
+            return Err(js_object(&[("ok", JsValue::FALSE), ("error", error.into())]));
+        }
+
+        let hex = self.truncate(&format!("{:x}", self.digest_async(data).await), md5_length);
+        Ok(js_object(&[("ok", JsValue::TRUE), ("hex", JsValue::from_str(&hex))]))
     }
 
-    /// Begin an incremental (streaming) MD5 session identified by `session_id`.
+    /// Like `calculate_md5_async`, but double-checks the hex encoding before returning:
+    /// the full hex must decode back to the raw digest bytes and the truncated result
+    /// must be a prefix of it. Any inconsistency rejects instead of panicking.
     #[wasm_bindgen]
-    pub fn start_incremental_md5(&self, session_id: &str) {
-        HASH_STATES.with(|states| {
-            states.borrow_mut().insert(session_id.to_string(), Md5::new());
-        });
-        console_log!(self.enable_log, "Started incremental MD5 session: {}", session_id);
+    pub async fn calculate_md5_checked_async(&self, data: &[u8], md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        let mut hasher = Md5::new();
+        self.feed_chunked(data, |chunk| hasher.update(chunk)).await;
+
+        let hash = hasher.finalize();
+        let hash_string = format!("{:x}", hash);
+
+        match decode_hex(&hash_string) {
+            Some(bytes) if bytes.as_slice() == hash.as_slice() => {}
+            _ => return Err(js_error("MD5 hex encoding does not round-trip to the digest bytes")),
+        }
+
+        let truncated_hash = self.truncate(&hash_string, md5_length);
+        let expected_len = self.rounded_length(md5_length).min(hash_string.len());
+        if truncated_hash.len() != expected_len || !hash_string.starts_with(&truncated_hash) {
+            return Err(js_error("Truncated MD5 is not a prefix of the full digest"));
+        }
+
+        console_log!(self.enable_log, "Checked MD5 calculation completed: {}", truncated_hash);
+        Ok(truncated_hash)
     }
 
-    /// Feed data into an active incremental session. Returns true on success.
+    /// Compute the MD5 of the decompressed contents of a gzip stream (multi-member aware).
+    /// Inflates through a 64 KB buffer inside the shared chunk loop, so the decompressed data
+    /// is never held in memory at once and yields follow the calculator's policy over the
+    /// decompressed bytes. Corrupt or truncated gzip input rejects.
     #[wasm_bindgen]
-    pub fn update_incremental_md5(&self, session_id: &str, data: &[u8]) -> bool {
-        HASH_STATES.with(|states| {
-            let mut map = states.borrow_mut();
-            if let Some(hasher) = map.get_mut(session_id) {
-                hasher.update(data);
-                console_log!(self.enable_log, "Updated incremental MD5 session: {}, data length: {}", session_id, data.len());
-                true
+    pub async fn calculate_md5_gunzip_async(&self, gzipped: &[u8], md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        console_log!(self.enable_log, "Starting gunzip MD5 calculation, compressed length: {}", gzipped.len());
+
+        let mut decoder = flate2::read::MultiGzDecoder::new(gzipped);
+        let mut hasher = Md5::new();
+        let mut buffer = vec![0u8; 64 * 1024];
+        let mut total = 0usize;
+        let mut failure = None;
+        // The decompressed length is not known up front, so chunk it like a large input.
+        let step = |max: usize| {
+            let mut done = 0;
+            while done < max {
+                let want = buffer.len().min(max - done);
+                match decoder.read(&mut buffer[..want]) {
+                    Ok(0) => break,
+                    Ok(read) => {
+                        hasher.update(&buffer[..read]);
+                        done += read;
+                    }
+                    Err(e) => {
+                        failure = Some(js_error(&format!("Invalid gzip data: {}", e)));
+                        break;
+                    }
+                }
+            }
+            self.count_hashed(done as u64);
+            total += done;
+            if failure.is_some() {
+                0
             } else {
-                console_log!(self.enable_log, "Incremental MD5 session not found: {}", session_id);
-                false
+                done
             }
-        })
+        };
+        self.feed_steps(usize::MAX, step, None, None).await;
+        if let Some(error) = failure {
+            return Err(error);
+        }
+
+        let hash_string = format!("{:x}", hasher.finalize());
+        let truncated_hash = self.truncate(&hash_string, md5_length);
+
+        console_log!(self.enable_log, "Gunzip MD5 calculation completed: {}, decompressed length: {}", truncated_hash, total);
+        Ok(truncated_hash)
     }
 
-    /// Finalize the session and return the hex digest.
-    /// Returns an empty string and logs a warning if the session does not exist.
+    /// Hash a set of named buffers (`{ name: string, bytes: Uint8Array }[]`) into a manifest.
+    /// Returns `{ entries: { name, hex }[], digest }` with entries sorted by name; `digest` is
+    /// the MD5 of the concatenated `name\0hex\n` lines in that order, so the same set of
+    /// files always yields the same fingerprint. Duplicate names or malformed entries reject.
     #[wasm_bindgen]
-    pub fn finalize_incremental_md5(&self, session_id: &str, md5_length: usize) -> String {
-        HASH_STATES.with(|states| {
-            let mut map = states.borrow_mut();
-            if let Some(hasher) = map.remove(session_id) {
-                let hash = hasher.finalize();
-                let hash_string = format!("{:x}", hash);
-                let truncated_hash = Self::truncate_hash(&hash_string, md5_length);
+    pub async fn calculate_manifest_async(&self, entries: JsValue, md5_length: usize) -> Result<JsValue, JsValue> {
+        check_md5_length(md5_length)?;
+        if !js_sys::Array::is_array(&entries) {
+            return Err(js_error("Manifest entries must be an array"));
+        }
+        let entries = js_sys::Array::from(&entries);
 
-                console_log!(self.enable_log, "Finalized incremental MD5 session: {}, result: {}", session_id, truncated_hash);
-                truncated_hash
-            } else {
-                console_log!(self.enable_log, "WARNING: Incremental MD5 session not found for finalization: {}", session_id);
-                String::new()
+        let mut named = Vec::with_capacity(entries.length() as usize);
+        for (index, entry) in entries.iter().enumerate() {
+            let name = js_sys::Reflect::get(&entry, &JsValue::from_str("name"))?
+                .as_string()
+                .ok_or_else(|| js_error(&format!("Manifest entry {} has no string `name`", index)))?;
+            let bytes = js_sys::Reflect::get(&entry, &JsValue::from_str("bytes"))?
+                .dyn_into::<js_sys::Uint8Array>()
+                .map_err(|_| js_error(&format!("Manifest entry `{}` has no Uint8Array `bytes`", name)))?;
+            named.push((name, bytes));
+        }
+
+        named.sort_by(|a, b| a.0.cmp(&b.0));
+        if let Some(pair) = named.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(js_error(&format!("Duplicate manifest entry name: {}", pair[0].0)));
+        }
+
+        let results = js_sys::Array::new();
+        let mut manifest_hasher = Md5::new();
+        for (name, bytes) in &named {
+            // Copy one entry at a time so only a single buffer lives in WASM memory.
+            let data = bytes.to_vec();
+            let mut hasher = Md5::new();
+            self.feed_chunked(&data, |chunk| hasher.update(chunk)).await;
+            let hex = self.truncate(&format!("{:x}", hasher.finalize()), md5_length);
+
+            manifest_hasher.update(name.as_bytes());
+            manifest_hasher.update([0u8]);
+            manifest_hasher.update(hex.as_bytes());
+            manifest_hasher.update(b"\n");
+
+            results.push(&js_object(&[
+                ("name", JsValue::from_str(name)),
+                ("hex", JsValue::from_str(&hex)),
+            ]));
+        }
+
+        let digest = self.truncate(&format!("{:x}", manifest_hasher.finalize()), md5_length);
+        console_log!(self.enable_log, "Manifest MD5 calculation completed: {} entries, digest: {}", named.len(), digest);
+        self.warn_if_collision_prone(md5_length, named.len() as u64);
+
+        Ok(js_object(&[
+            ("entries", results.into()),
+            ("digest", JsValue::from_str(&digest)),
+        ]))
+    }
+
+    /// Low-memory batch hashing driven by callbacks: `next_buffer()` returns the next
+    /// `Uint8Array` (or a Promise of one), or `null`/`undefined` when done. Each buffer is
+    /// hashed, reported via `on_result(index, hex)` and dropped before the next one is
+    /// requested, so peak WASM memory stays at a single buffer. Resolves with the count.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_batch_streaming_async(
+        &self,
+        next_buffer: js_sys::Function,
+        on_result: js_sys::Function,
+        md5_length: usize,
+    ) -> Result<u32, JsValue> {
+        check_md5_length(md5_length)?;
+        let mut index = 0u32;
+
+        loop {
+            let mut next = next_buffer.call0(&JsValue::NULL)?;
+            if next.is_instance_of::<js_sys::Promise>() {
+                next = wasm_bindgen_futures::JsFuture::from(js_sys::Promise::from(next)).await?;
             }
-        })
+            if next.is_null() || next.is_undefined() {
+                break;
+            }
+
+            let data = next
+                .dyn_into::<js_sys::Uint8Array>()
+                .map_err(|_| js_error(&format!("next_buffer() returned a non-Uint8Array value at index {}", index)))?
+                .to_vec();
+
+            let mut hasher = Md5::new();
+            self.feed_chunked(&data, |chunk| hasher.update(chunk)).await;
+            drop(data);
+
+            let hex = self.truncate(&format!("{:x}", hasher.finalize()), md5_length);
+            on_result.call2(&JsValue::NULL, &JsValue::from(index), &JsValue::from_str(&hex))?;
+            index += 1;
+
+            self.yield_point().await;
+        }
+
+        console_log!(self.enable_log, "Streaming batch MD5 calculation completed: {} buffers", index);
+        self.warn_if_collision_prone(md5_length, u64::from(index));
+        Ok(index)
     }
 
-    /// Cancel and discard an active incremental session.
+    /// Hash many buffers in one call, resolving with their hex digests in input order; an
+    /// empty list resolves with an empty array. Yields between buffers (and within large
+    /// ones) so the UI stays responsive. `md5_length` follows `calculate_md5_async` and is
+    /// validated up front.
     #[wasm_bindgen]
-    pub fn cancel_incremental_md5(&self, session_id: &str) -> bool {
-        HASH_STATES.with(|states| {
-            let removed = states.borrow_mut().remove(session_id).is_some();
-            if removed {
-                console_log!(self.enable_log, "Cancelled incremental MD5 session: {}", session_id);
-            } else {
-                console_log!(self.enable_log, "Incremental MD5 session not found for cancellation: {}", session_id);
+    pub async fn calculate_md5_batch_async(&self, files: Vec<js_sys::Uint8Array>, md5_length: usize) -> Result<Vec<String>, JsValue> {
+        check_md5_length(md5_length)?;
+        let mut hexes = Vec::with_capacity(files.len());
+        for (index, file) in files.iter().enumerate() {
+            if index > 0 {
+                self.yield_point().await;
             }
-            removed
-        })
+            let hex = format!("{:x}", self.digest_async(&file.to_vec()).await);
+            hexes.push(self.truncate(&hex, md5_length));
+        }
+
+        console_log!(self.enable_log, "Batch MD5 calculation completed: {} buffers", hexes.len());
+        self.warn_if_collision_prone(md5_length, hexes.len() as u64);
+        Ok(hexes)
     }
 
-    fn truncate_hash(hash_string: &str, md5_length: usize) -> String {
-        match md5_length {
-            16 => hash_string[..16].to_string(),
-            32 => hash_string.to_string(),
-            _ => hash_string[..std::cmp::min(md5_length, hash_string.len())].to_string(),
+    /// Hash only `data[offset..offset + length]`, borrowing the range rather than copying
+    /// it. Rejects when the range does not fit inside `data`, or for an invalid `md5_length`.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_range_async(&self, data: &[u8], offset: usize, length: usize, md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        let range = offset
+            .checked_add(length)
+            .and_then(|end| data.get(offset..end))
+            .ok_or_else(|| js_error(&format!("Range {}+{} is out of bounds for {} bytes", offset, length, data.len())))?;
+
+        let truncated_hash = self.truncate(&format!("{:x}", self.digest_async(range).await), md5_length);
+        console_log!(self.enable_log, "Range MD5 calculation completed: {}, offset: {}, length: {}", truncated_hash, offset, length);
+        Ok(truncated_hash)
+    }
+
+    /// Split `data` into `region_count` regions and hash each independently. Region sizes
+    /// differ by at most one byte: the first `data.length % region_count` regions take one
+    /// extra byte each, so no region is a straggler. Resolves with
+    /// `{ regions: [{ start, end, hex }], combined }`, where `combined` is the MD5 of the
+    /// regions' raw digests concatenated in order. `combined` is *not* the MD5 of `data`;
+    /// MD5 cannot be parallelised, so this defines a separate hash-list digest instead.
+    /// The regions are hashed one after another on the calling thread. Because each depends
+    /// only on its own bytes, callers can compute the same regions in separate workers and
+    /// combine them, and get the same result.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_regions_async(&self, data: &[u8], region_count: usize, md5_length: usize) -> Result<JsValue, JsValue> {
+        check_md5_length(md5_length)?;
+        if region_count == 0 {
+            return Err(js_error("region_count must be greater than zero"));
+        }
+
+        let (base_size, remainder) = (data.len() / region_count, data.len() % region_count);
+        let regions = js_sys::Array::new();
+        let mut combined = Md5::new();
+        let mut start = 0;
+        for index in 0..region_count {
+            if index > 0 {
+                self.yield_point().await;
+            }
+            let end = start + base_size + usize::from(index < remainder);
+            let digest = self.digest_async(&data[start..end]).await;
+            combined.update(digest);
+            regions.push(&js_object(&[
+                ("start", JsValue::from(start as f64)),
+                ("end", JsValue::from(end as f64)),
+                ("hex", JsValue::from(self.truncate(&format!("{:x}", digest), md5_length))),
+            ]));
+            start = end;
+        }
+
+        let combined = self.truncate(&format!("{:x}", combined.finalize()), md5_length);
+        Ok(js_object(&[("regions", regions.into()), ("combined", JsValue::from(combined))]))
+    }
+
+    /// Hash each buffer and pack the first `bytes_per_digest` (1..=16) raw digest bytes of
+    /// every result back-to-back, in input order: the output is `buffers.length * bytes_per_digest`
+    /// bytes. Empty buffers are not special-cased; their slot holds the MD5 of zero bytes.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_batch_packed_async(
+        &self,
+        buffers: Vec<js_sys::Uint8Array>,
+        bytes_per_digest: usize,
+    ) -> Result<Vec<u8>, JsValue> {
+        if bytes_per_digest == 0 || bytes_per_digest > 16 {
+            return Err(js_error(&format!("bytes_per_digest must be between 1 and 16, got {}", bytes_per_digest)));
+        }
+
+        let mut packed = Vec::with_capacity(buffers.len() * bytes_per_digest);
+        for buffer in &buffers {
+            let data = buffer.to_vec();
+            let mut hasher = Md5::new();
+            self.feed_chunked(&data, |chunk| hasher.update(chunk)).await;
+            packed.extend_from_slice(&hasher.finalize()[..bytes_per_digest]);
+            self.yield_point().await;
+        }
+
+        console_log!(self.enable_log, "Packed batch MD5 calculation completed: {} buffers", buffers.len());
+        Ok(packed)
+    }
+
+    /// Hash each buffer and compare it, in constant time, against the expected hex at the same
+    /// index (either case, surrounding whitespace ignored); resolves with an array of one boolean
+    /// per buffer. Malformed expected hex yields `false` for that index; rejects if the lists
+    /// differ in length.
+    #[wasm_bindgen]
+    pub async fn verify_batch_async(&self, buffers: Vec<js_sys::Uint8Array>, expected: Vec<String>) -> Result<js_sys::Array, JsValue> {
+        if buffers.len() != expected.len() {
+            return Err(js_error(&format!(
+                "verify_batch_async got {} buffers but {} expected hashes",
+                buffers.len(),
+                expected.len()
+            )));
+        }
+
+        let results = js_sys::Array::new();
+        let mut matched_count = 0;
+        for (buffer, expected_hex) in buffers.iter().zip(&expected) {
+            let digest = self.digest_async(&buffer.to_vec()).await;
+            let matched = decode_hex(expected_hex.trim()).is_some_and(|bytes| constant_time_eq(&digest, &bytes));
+            matched_count += usize::from(matched);
+            results.push(&JsValue::from_bool(matched));
+            self.yield_point().await;
+        }
+
+        console_log!(self.enable_log, "Batch verification completed: {}/{} matched", matched_count, expected.len());
+        Ok(results)
+    }
+
+    /// Whether `data` hashes to `expected`: a 32-char full digest or a 16-char prefix, in
+    /// either case (surrounding whitespace ignored), compared in constant time. Any other
+    /// length, or non-hex characters, gives `false`.
+    #[wasm_bindgen]
+    pub async fn verify_md5_async(&self, data: &[u8], expected: &str) -> bool {
+        let expected = expected.trim();
+        if expected.len() != 16 && expected.len() != 32 {
+            return false;
+        }
+        let expected = match decode_hex(expected) {
+            Some(bytes) => bytes,
+            None => return false,
+        };
+        let digest = self.digest_async(data).await;
+        constant_time_eq(&digest[..expected.len()], &expected)
+    }
+
+    /// MD5 over the raw bytes of several hex digests (either case) concatenated in the given
+    /// order with no delimiter, so the result is order-sensitive. With full part digests this
+    /// is the S3 multipart ETag hash (without its `-<parts>` suffix). Rejects any entry that is
+    /// empty or not well-formed hex.
+    #[wasm_bindgen]
+    pub fn combine_digests(&self, hexes: Vec<String>, md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        let mut hasher = Md5::new();
+        for hex in &hexes {
+            let bytes = decode_hex(hex.trim())
+                .filter(|bytes| !bytes.is_empty())
+                .ok_or_else(|| js_error(&format!("Invalid hex digest: {}", hex)))?;
+            hasher.update(&bytes);
+        }
+        Ok(self.truncate(&format!("{:x}", hasher.finalize()), md5_length))
+    }
+
+    /// Root of a binary Merkle tree over already-computed leaf digests (32-char hex, either
+    /// case). Each parent is the MD5 of its children's raw 16-byte digests concatenated; a
+    /// level with an odd node count pairs its last node with itself. A single leaf is its own
+    /// root. Rejects an empty list or a leaf that is not a 16-byte hex digest.
+    #[wasm_bindgen]
+    pub async fn calculate_merkle_root_async(&self, file_digests: Vec<String>) -> Result<String, JsValue> {
+        let mut level = file_digests
+            .iter()
+            .map(|hex| {
+                decode_hex(hex.trim())
+                    .filter(|bytes| bytes.len() == 16)
+                    .ok_or_else(|| js_error(&format!("Invalid MD5 leaf digest: {}", hex)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if level.is_empty() {
+            return Err(js_error("Merkle root needs at least one leaf digest"));
+        }
+
+        while level.len() > 1 {
+            level = level
+                .chunks(2)
+                .map(|pair| {
+                    let mut hasher = Md5::new();
+                    hasher.update(&pair[0]);
+                    hasher.update(pair.get(1).unwrap_or(&pair[0]));
+                    hasher.finalize().to_vec()
+                })
+                .collect();
+            self.yield_point().await;
+        }
+
+        let root = bytes_to_hex(&level[0], false);
+        console_log!(self.enable_log, "Merkle root over {} leaves: {}", file_digests.len(), root);
+        Ok(root)
+    }
+
+    /// Compute the full MD5 formatted as an HTTP ETag: `"<hex>"`, or `W/"<hex>"` when `weak`.
+    /// Always uses the full 32-char hex.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_etag_async(&self, data: &[u8], weak: bool) -> String {
+        let hex = format!("{:x}", self.digest_async(data).await);
+        if weak {
+            format!("W/\"{}\"", hex)
+        } else {
+            format!("\"{}\"", hex)
+        }
+    }
+
+    /// Whether `etag` (as sent by a server: optionally weak `W/`, optionally quoted, either
+    /// hex case) names the full MD5 of `data`, compared in constant time. ETags that are not
+    /// 32 hex chars once unwrapped are opaque server tokens and give `false` without hashing.
+    #[wasm_bindgen]
+    pub async fn matches_etag_async(&self, data: &[u8], etag: &str) -> bool {
+        let etag = etag.trim();
+        let etag = etag.strip_prefix("W/").unwrap_or(etag);
+        let etag = etag.strip_prefix('"').and_then(|rest| rest.strip_suffix('"')).unwrap_or(etag);
+        if !is_valid_md5_hex(etag) {
+            return false;
+        }
+        let computed = format!("{:x}", self.digest_async(data).await);
+        constant_time_eq(computed.as_bytes(), etag.to_ascii_lowercase().as_bytes())
+    }
+
+    /// Hash an interleaving of two sources: each byte of `pattern` (0 = `a`, 1 = `b`) selects
+    /// the source of the next chunk of up to `chunk_size` bytes, read sequentially from that
+    /// source. A source's final chunk may be short; selecting an exhausted source, a pattern
+    /// value other than 0/1, or a zero `chunk_size` rejects.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_interleaved_async(
+        &self,
+        a: &[u8],
+        b: &[u8],
+        pattern: &[u8],
+        chunk_size: usize,
+        md5_length: usize,
+    ) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        if chunk_size == 0 {
+            return Err(js_error("chunk_size must be greater than zero"));
+        }
+
+        // Resolve the pattern into `(source, start, end)` segments first, so a bad step rejects
+        // before anything is hashed.
+        let sources = [a, b];
+        let mut offsets = [0usize; 2];
+        let mut segments = Vec::with_capacity(pattern.len());
+        for (step, &selector) in pattern.iter().enumerate() {
+            let source = match selector {
+                0 | 1 => selector as usize,
+                _ => return Err(js_error(&format!("Invalid pattern value {} at step {}", selector, step))),
+            };
+            let data = sources[source];
+            let start = offsets[source];
+            if start >= data.len() {
+                return Err(js_error(&format!("Pattern step {} reads past the end of source {}", step, source)));
+            }
+            let end = data.len().min(start + chunk_size);
+            segments.push((source, start, end));
+            offsets[source] = end;
+        }
+
+        let total = segments.iter().map(|&(_, start, end)| end - start).sum();
+        let mut hasher = Md5::new();
+        let mut segment = 0;
+        let mut within = 0;
+        self.feed_ranges(
+            total,
+            |range| {
+                let mut remaining = range.len();
+                while remaining > 0 {
+                    let (source, start, end) = segments[segment];
+                    let from = start + within;
+                    let take = remaining.min(end - from);
+                    hasher.update(&sources[source][from..from + take]);
+                    remaining -= take;
+                    within += take;
+                    if from + take == end {
+                        segment += 1;
+                        within = 0;
+                    }
+                }
+            },
+            None,
+            None,
+        )
+        .await;
+
+        let truncated_hash = self.truncate(&format!("{:x}", hasher.finalize()), md5_length);
+        console_log!(self.enable_log, "Interleaved MD5 calculation completed: {}", truncated_hash);
+        Ok(truncated_hash)
+    }
+
+    /// Hash `data` and estimate its Shannon entropy in the same pass, from a 256-bin byte
+    /// histogram. Returns `{ hex, entropy_bits_per_byte }`: ~0 for constant data, close to
+    /// 8 for compressed or encrypted data; 0 for empty input.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_with_entropy_async(&self, data: &[u8], md5_length: usize) -> Result<JsValue, JsValue> {
+        check_md5_length(md5_length)?;
+        let mut hasher = Md5::new();
+        let mut histogram = [0u64; 256];
+        self.feed_chunked(data, |chunk| {
+            hasher.update(chunk);
+            for &byte in chunk {
+                histogram[byte as usize] += 1;
+            }
+        })
+        .await;
+
+        let total = data.len() as f64;
+        let entropy = histogram
+            .iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| {
+                let p = count as f64 / total;
+                -p * p.log2()
+            })
+            .sum::<f64>();
+
+        let hex = self.truncate(&format!("{:x}", hasher.finalize()), md5_length);
+        console_log!(self.enable_log, "MD5 with entropy completed: {}, entropy: {:.3}", hex, entropy);
+
+        Ok(js_object(&[
+            ("hex", JsValue::from_str(&hex)),
+            ("entropy_bits_per_byte", JsValue::from_f64(entropy)),
+        ]))
+    }
+
+    /// Compute MD5 and count occurrences of the byte `target` in the same chunk loop:
+    /// `{ hex, count }`, e.g. null bytes as a binary-file heuristic.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_with_byte_count_async(&self, data: &[u8], target: u8, md5_length: usize) -> Result<JsValue, JsValue> {
+        check_md5_length(md5_length)?;
+        let mut hasher = Md5::new();
+        let mut count = 0usize;
+        self.feed_chunked(data, |chunk| {
+            hasher.update(chunk);
+            count += chunk.iter().filter(|&&byte| byte == target).count();
+        })
+        .await;
+
+        let hex = self.truncate(&format!("{:x}", hasher.finalize()), md5_length);
+        Ok(js_object(&[("hex", JsValue::from_str(&hex)), ("count", JsValue::from(count as f64))]))
+    }
+
+    /// The 16 raw MD5 digest bytes of `data` as a `Uint8Array`, for APIs that want bytes
+    /// rather than hex.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_bytes_async(&self, data: &[u8]) -> Vec<u8> {
+        self.digest_async(data).await.to_vec()
+    }
+
+    /// Legacy `md5(md5(data))`: the outer MD5 hashes the 32-char lowercase *hex string* of the
+    /// inner digest (as ASCII bytes), not its 16 raw bytes. Only the final digest is
+    /// truncated to `md5_length`.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_double_async(&self, data: &[u8], md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        let inner = format!("{:x}", self.digest_async(data).await);
+        self.count_hashed(inner.len() as u64);
+        let truncated_hash = self.truncate(&format!("{:x}", Md5::digest(inner.as_bytes())), md5_length);
+        console_log!(self.enable_log, "Double MD5 calculation completed: {}, data length: {}", truncated_hash, data.len());
+        Ok(truncated_hash)
+    }
+
+    /// Smoke test for a freshly loaded binary: hash the RFC 1321 test vectors (plus a million
+    /// `a`s, to exercise the chunked loop) through both the one-shot and incremental paths
+    /// and compare with the published digests. Resolves true if all match; mismatches are
+    /// logged when logging is enabled. Not counted in `total_bytes_hashed`.
+    #[wasm_bindgen]
+    pub async fn self_test_async(&self) -> bool {
+        let bytes_hashed = self.bytes_hashed.get();
+        let million_a = vec![b'a'; 1_000_000];
+        let vectors = SELF_TEST_VECTORS.iter().copied().chain([(&million_a[..], "7707d6ae4e027c70eea2a935c2296f21")]);
+
+        let mut passed = true;
+        for (input, expected) in vectors {
+            let one_shot = format!("{:x}", self.digest_async(input).await);
+            let mut session = SessionHasher::new();
+            session.update(input);
+            let incremental = format!("{:x}", session.finalize());
+
+            for (path, hex) in [("one-shot", one_shot), ("incremental", incremental)] {
+                if hex != expected {
+                    passed = false;
+                    console_log!(self.enable_log, "WARNING: Self-test mismatch ({}), input length {}: expected {}, got {}", path, input.len(), expected, hex);
+                }
+            }
+        }
+
+        self.bytes_hashed.set(bytes_hashed);
+        console_log!(self.enable_log, "Self-test {}", if passed { "passed" } else { "FAILED" });
+        passed
+    }
+
+    /// IEEE CRC-32 of `data` alone, fed through the same chunked, yielding loop as MD5: a fast
+    /// non-cryptographic checksum for bucketing candidates before a full MD5 comparison.
+    #[wasm_bindgen]
+    pub async fn calculate_crc32_async(&self, data: &[u8]) -> u32 {
+        let mut crc = crc32fast::Hasher::new();
+        self.feed_chunked(data, |chunk| crc.update(chunk)).await;
+        let crc32 = crc.finalize();
+        console_log!(self.enable_log, "CRC-32 calculation completed: {:08x}, data length: {}", crc32, data.len());
+        crc32
+    }
+
+    /// Compute MD5 and the IEEE CRC-32 of `data` in the same chunk loop: `{ md5, crc32 }`,
+    /// with `crc32` as an unsigned 32-bit number for a cheap pre-check before comparing MD5.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_crc_async(&self, data: &[u8], md5_length: usize) -> Result<JsValue, JsValue> {
+        check_md5_length(md5_length)?;
+        let mut hasher = Md5::new();
+        let mut crc = crc32fast::Hasher::new();
+        self.feed_chunked(data, |chunk| {
+            hasher.update(chunk);
+            crc.update(chunk);
+        })
+        .await;
+
+        let hex = self.truncate(&format!("{:x}", hasher.finalize()), md5_length);
+        let crc32 = crc.finalize();
+        console_log!(self.enable_log, "MD5 with CRC-32 completed: {}, crc32: {:08x}", hex, crc32);
+        Ok(js_object(&[("md5", JsValue::from_str(&hex)), ("crc32", JsValue::from(crc32))]))
+    }
+
+    /// Compute the MD5 once and return both casings as `{ lower, upper }`, truncated per
+    /// `md5_length`.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_both_case_async(&self, data: &[u8], md5_length: usize) -> Result<JsValue, JsValue> {
+        check_md5_length(md5_length)?;
+        let lower = self.truncate(&format!("{:x}", self.digest_async(data).await), md5_length);
+        let upper = lower.to_ascii_uppercase();
+        Ok(js_object(&[
+            ("lower", JsValue::from_str(&lower)),
+            ("upper", JsValue::from_str(&upper)),
+        ]))
+    }
+
+    /// Standard padded base64 of the 16 raw digest bytes (24 chars), as used by the
+    /// `Content-MD5` header. Always the full 128-bit digest; there is no `md5_length`.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_base64_async(&self, data: &[u8]) -> String {
+        encode_base64(&self.digest_async(data).await)
+    }
+
+    /// Compute the MD5 of `a XOR b` (equal lengths required), XOR-ing through a small
+    /// scratch buffer so the combined buffer is never materialized. Mismatched lengths reject.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_xor_async(&self, a: &[u8], b: &[u8], md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        if a.len() != b.len() {
+            return Err(js_error(&format!("XOR inputs must have equal lengths, got {} and {}", a.len(), b.len())));
+        }
+
+        let mut hasher = Md5::new();
+        let mut scratch = vec![0u8; 64 * 1024];
+        let piece = scratch.len();
+        self.feed_ranges(
+            a.len(),
+            |range| {
+                for (left, right) in a[range.clone()].chunks(piece).zip(b[range].chunks(piece)) {
+                    let mixed = &mut scratch[..left.len()];
+                    for ((out, x), y) in mixed.iter_mut().zip(left).zip(right) {
+                        *out = x ^ y;
+                    }
+                    hasher.update(&*mixed);
+                }
+            },
+            None,
+            None,
+        )
+        .await;
+
+        let truncated_hash = self.truncate(&format!("{:x}", hasher.finalize()), md5_length);
+        console_log!(self.enable_log, "XOR MD5 calculation completed: {}", truncated_hash);
+        Ok(truncated_hash)
+    }
+
+    /// Describe how `calculate_md5_async` would process `data_len` bytes without hashing
+    /// anything: `{ chunked, chunk_size, expected_yields, estimated_peak_bytes }`.
+    /// `chunk_size` is the whole length for direct hashing; `estimated_peak_bytes` counts the
+    /// input copy in WASM memory plus the hasher state.
+    #[wasm_bindgen]
+    pub fn plan_md5(&self, data_len: usize) -> JsValue {
+        let chunk_size = self.chunk_size_for(data_len);
+        let estimated_peak_bytes = data_len + std::mem::size_of::<Md5>();
+        js_object(&[
+            ("chunked", JsValue::from_bool(chunk_size.is_some())),
+            ("chunk_size", JsValue::from(chunk_size.unwrap_or(data_len) as f64)),
+            ("expected_yields", JsValue::from(self.expected_yields(data_len) as f64)),
+            ("estimated_peak_bytes", JsValue::from(estimated_peak_bytes as f64)),
+        ])
+    }
+
+    /// The `{ index, start, end }` byte ranges (end exclusive) `calculate_md5_async` feeds to
+    /// the hasher, one call each, for `data_len` bytes under the current settings. The last
+    /// range holds the remainder; a single range covers inputs hashed directly, including an
+    /// empty one.
+    #[wasm_bindgen]
+    pub fn describe_split(&self, data_len: usize) -> JsValue {
+        let chunk_size = self.chunk_size_for(data_len).unwrap_or(data_len.max(1));
+        let ranges = js_sys::Array::new();
+        let mut start = 0;
+        loop {
+            let end = data_len.min(start + chunk_size);
+            ranges.push(&js_object(&[
+                ("index", JsValue::from(ranges.length())),
+                ("start", JsValue::from(start as f64)),
+                ("end", JsValue::from(end as f64)),
+            ]));
+            if end == data_len {
+                break;
+            }
+            start = end;
+        }
+        ranges.into()
+    }
+
+    /// Approximate probability that at least two of `expected_items` distinct inputs share a
+    /// digest truncated to `md5_length` hex chars (after the length-rounding policy), by the
+    /// birthday bound `1 - exp(-n(n-1) / 2^(bits+1))`. Pure math; nothing is hashed.
+    #[wasm_bindgen]
+    pub fn truncation_collision_estimate(&self, md5_length: usize, expected_items: u64) -> f64 {
+        if expected_items < 2 {
+            return 0.0;
+        }
+        let bits = 4 * self.rounded_length(md5_length).min(32) as i32;
+        let n = expected_items as f64;
+        -(-n * (n - 1.0) / 2f64.powi(bits + 1)).exp_m1()
+    }
+
+    /// Number of times `calculate_md5_async` yields to the event loop for `data_len` bytes
+    /// under the current settings. Extra yields from `set_max_block_ms` depend on timing and
+    /// are not counted.
+    #[wasm_bindgen]
+    pub fn expected_yield_count(&self, data_len: usize) -> usize {
+        self.expected_yields(data_len)
+    }
+
+    /// Compute MD5 and return it with a copy of the leading input bytes in one call:
+    /// `{ hex, prefix }`, where `prefix` is a `Uint8Array` of `data[..min(prefix_len, len)]`.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_with_prefix_async(&self, data: &[u8], prefix_len: usize, md5_length: usize) -> Result<JsValue, JsValue> {
+        check_md5_length(md5_length)?;
+        let hex = self.truncate(&format!("{:x}", self.digest_async(data).await), md5_length);
+        let prefix = js_sys::Uint8Array::from(&data[..prefix_len.min(data.len())]);
+        Ok(js_object(&[("hex", JsValue::from_str(&hex)), ("prefix", prefix.into())]))
+    }
+
+    /// Compute MD5, truncate to `md5_length` hex chars, then render in `format`. With an odd
+    /// length the colon formats end in a single hex char.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_formatted_async(&self, data: &[u8], md5_length: usize, format: DigestFormat) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        let render = DIGEST_FORMATS
+            .iter()
+            .find(|&&(variant, _, _)| variant == format)
+            .map(|&(_, _, render)| render)
+            .ok_or_else(|| js_error(&format!("Unsupported digest format: {:?}", format)))?;
+        let hex = self.truncate(&format!("{:x}", self.digest_async(data).await), md5_length);
+        Ok(render(hex))
+    }
+
+    /// Hash text bytes with a leading UTF-8 BOM (`EF BB BF`) skipped, matching servers that
+    /// strip it; input without a BOM is hashed unchanged.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_text_nobom_async(&self, data: &[u8], md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        let text = data.strip_prefix(&[0xEF, 0xBB, 0xBF][..]).unwrap_or(data);
+        Ok(self.truncate(&format!("{:x}", self.digest_async(text).await), md5_length))
+    }
+
+    /// Decode `b64` and hash the bytes. Accepts the standard (`+/`) and URL-safe (`-_`)
+    /// alphabets, with or without `=` padding; anything else (including whitespace) rejects.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_from_base64_async(&self, b64: &str, md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        let data = decode_base64(b64)?;
+        Ok(self.truncate(&format!("{:x}", self.digest_async(&data).await), md5_length))
+    }
+
+    /// MD5 of every `stride`-th byte of `data` (`data[0], data[stride], data[2 * stride], ...`),
+    /// a cheap deterministic fingerprint of large media. A stride of 1 is a normal hash;
+    /// zero rejects.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_strided_async(&self, data: &[u8], stride: usize, md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        if stride == 0 {
+            return Err(js_error("stride must be greater than zero"));
+        }
+
+        let mut hasher = Md5::new();
+        if stride == 1 {
+            self.feed_chunked(data, |chunk| hasher.update(chunk)).await;
+        } else {
+            // Gather the sampled bytes of each chunk of input (from the first multiple of
+            // `stride` inside it) through a 64 KB buffer.
+            let mut samples = Vec::with_capacity(64 * 1024);
+            let mut position = 0usize;
+            let step = |max: usize| {
+                let start = position;
+                let end = data.len().min(start.saturating_add(max));
+                let first = start.div_ceil(stride).saturating_mul(stride);
+                for &sample in data[first.min(end)..end].iter().step_by(stride) {
+                    samples.push(sample);
+                    if samples.len() == samples.capacity() {
+                        hasher.update(&samples);
+                        self.count_hashed(samples.len() as u64);
+                        samples.clear();
+                    }
+                }
+                hasher.update(&samples);
+                self.count_hashed(samples.len() as u64);
+                samples.clear();
+                position = end;
+                end - start
+            };
+            self.feed_steps(data.len(), step, None, None).await;
+        }
+        Ok(self.truncate(&format!("{:x}", hasher.finalize()), md5_length))
+    }
+
+    /// MD5 of the content of a framed container: `data` alternates `frame_interval` content
+    /// bytes with `skip_len` metadata bytes (content first), and only the content is hashed,
+    /// straight from `data`. A short trailing content run is hashed; a short trailing
+    /// metadata run is skipped. A zero `frame_interval` rejects.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_skip_frames_async(
+        &self,
+        data: &[u8],
+        frame_interval: usize,
+        skip_len: usize,
+        md5_length: usize,
+    ) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        if frame_interval == 0 {
+            return Err(js_error("frame_interval must be greater than zero"));
+        }
+        let frame_len = frame_interval.saturating_add(skip_len);
+
+        let mut hasher = Md5::new();
+        let mut position = 0usize;
+        let step = |max: usize| {
+            let start = position;
+            let end = data.len().min(start.saturating_add(max));
+            while position < end {
+                let frame_start = position - position % frame_len;
+                let content_end = frame_start.saturating_add(frame_interval);
+                position = if position < content_end {
+                    let next = content_end.min(end);
+                    hasher.update(&data[position..next]);
+                    self.count_hashed((next - position) as u64);
+                    next
+                } else {
+                    frame_start.saturating_add(frame_len).min(end)
+                };
+            }
+            end - start
+        };
+        self.feed_steps(data.len(), step, None, None).await;
+        Ok(self.truncate(&format!("{:x}", hasher.finalize()), md5_length))
+    }
+
+    /// MD5 of a run-length-encoded payload's expansion, without materializing it. `pairs` is
+    /// a sequence of 5-byte runs: the byte value, then its repeat count as a little-endian
+    /// `u32` (a zero count contributes nothing). Each run is hashed in 64 KB bursts from one
+    /// filled buffer, yielding every 2 MB of expanded output. Rejects if `pairs.length` is not
+    /// a multiple of 5.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_rle_async(&self, pairs: &[u8], md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        const BURST: usize = 64 * 1024;
+        if !pairs.len().is_multiple_of(5) {
+            return Err(js_error(&format!("RLE input must be 5-byte runs, got {} bytes", pairs.len())));
+        }
+
+        let run_len = |run: &[u8]| u32::from_le_bytes([run[1], run[2], run[3], run[4]]) as usize;
+        let total = pairs.chunks_exact(5).fold(0usize, |total, run| total.saturating_add(run_len(run)));
+
+        let mut hasher = Md5::new();
+        let mut burst = [0u8; BURST];
+        let mut runs = pairs.chunks_exact(5);
+        let mut remaining = 0usize;
+        let step = |max: usize| {
+            let mut done = 0;
+            while done < max {
+                if remaining == 0 {
+                    match runs.next() {
+                        Some(run) => {
+                            burst.fill(run[0]);
+                            remaining = run_len(run);
+                            continue;
+                        }
+                        None => break,
+                    }
+                }
+                let len = remaining.min(BURST).min(max - done);
+                hasher.update(&burst[..len]);
+                remaining -= len;
+                done += len;
+            }
+            self.count_hashed(done as u64);
+            done
+        };
+        self.feed_steps(total, step, None, None).await;
+        Ok(self.truncate(&format!("{:x}", hasher.finalize()), md5_length))
+    }
+
+    /// Compute MD5 and return it alongside caller-supplied metadata: `{ hex, mime, length }`.
+    /// `mime` is passed through unchanged (the type is not sniffed); `length` is `data.length`.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_tagged_async(&self, data: &[u8], mime: &str, md5_length: usize) -> Result<JsValue, JsValue> {
+        check_md5_length(md5_length)?;
+        let hex = self.truncate(&format!("{:x}", self.digest_async(data).await), md5_length);
+        Ok(js_object(&[
+            ("hex", JsValue::from_str(&hex)),
+            ("mime", JsValue::from_str(mime)),
+            ("length", JsValue::from(data.len() as f64)),
+        ]))
+    }
+
+    /// Compute MD5 and check whether `data` is valid UTF-8 in the same pass: `{ hex, is_utf8 }`.
+    /// A multibyte sequence split across hashing chunks is validated as a whole; input ending
+    /// mid-sequence is not UTF-8.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_with_utf8_check_async(&self, data: &[u8], md5_length: usize) -> Result<JsValue, JsValue> {
+        check_md5_length(md5_length)?;
+        let mut hasher = Md5::new();
+        let mut validator = utf8::Utf8Validator::default();
+        self.feed_chunked(data, |chunk| {
+            hasher.update(chunk);
+            validator.feed(chunk);
+        })
+        .await;
+
+        let hex = self.truncate(&format!("{:x}", hasher.finalize()), md5_length);
+        Ok(js_object(&[("hex", JsValue::from_str(&hex)), ("is_utf8", JsValue::from_bool(validator.finish()))]))
+    }
+
+    /// Hash `data` after passing each chunk through `transform(chunk)`, which must return a
+    /// `Uint8Array` (of any length) whose bytes are hashed in its place. Chunks follow the
+    /// calculator's chunking policy, so inputs up to 512 KiB arrive as one chunk; the transform
+    /// is not called after the first failure. Rejects if `transform` throws or returns
+    /// anything other than a `Uint8Array`.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_transform_async(
+        &self,
+        data: &[u8],
+        md5_length: usize,
+        transform: js_sys::Function,
+    ) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        let mut hasher = Md5::new();
+        let mut failure = None;
+        self.feed_chunked(data, |chunk| {
+            if failure.is_some() {
+                return;
+            }
+            match transform.call1(&JsValue::NULL, &js_sys::Uint8Array::from(chunk)) {
+                Ok(out) => match out.dyn_into::<js_sys::Uint8Array>() {
+                    Ok(bytes) => hasher.update(bytes.to_vec()),
+                    Err(_) => failure = Some(js_error("transform() must return a Uint8Array")),
+                },
+                Err(error) => failure = Some(error),
+            }
+        })
+        .await;
+
+        match failure {
+            Some(error) => Err(error),
+            None => Ok(self.truncate(&format!("{:x}", hasher.finalize()), md5_length)),
+        }
+    }
+
+    /// Challenge-response digest `MD5(nonce || secret || data)`, fed into one hasher in that
+    /// order so the concatenation never exists as a buffer.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_challenge_async(&self, nonce: &[u8], secret: &[u8], data: &[u8], md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        let mut hasher = Md5::new();
+        hasher.update(nonce);
+        hasher.update(secret);
+        self.feed_chunked(data, |chunk| hasher.update(chunk)).await;
+        Ok(self.truncate(&format!("{:x}", hasher.finalize()), md5_length))
+    }
+
+    /// MD5 of `data` framed as an SSH wire-format `string`: a 4-byte big-endian length,
+    /// then the bytes. WASM slices never exceed `u32::MAX` bytes, so the length is exact.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_ssh_string_async(&self, data: &[u8], md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        let mut hasher = Md5::new();
+        hasher.update((data.len() as u32).to_be_bytes());
+        self.feed_chunked(data, |chunk| hasher.update(chunk)).await;
+        Ok(self.truncate(&format!("{:x}", hasher.finalize()), md5_length))
+    }
+
+    /// The top `bits` bits of the MD5 digest as hex of the `ceil(bits / 8)` bytes that hold
+    /// them, with the unused low bits of the last byte zeroed (40 bits gives 10 hex chars).
+    /// `bits` above 128 is clamped; 0 rejects. Ignores the length-rounding policy.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_bits_async(&self, data: &[u8], bits: usize) -> Result<String, JsValue> {
+        if bits == 0 {
+            return Err(js_error("bits must be greater than zero"));
+        }
+        let bits = bits.min(128);
+        let mut bytes = self.digest_async(data).await[..bits.div_ceil(8)].to_vec();
+        if !bits.is_multiple_of(8) {
+            if let Some(last) = bytes.last_mut() {
+                *last &= 0xffu8 << (8 - bits % 8);
+            }
+        }
+        Ok(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+    }
+
+    /// MD5 of `data` up to, not including, the first `delimiter` byte: `{ hex, consumed }`,
+    /// where `consumed` is the delimiter's index, or the full length when it is absent.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_until_async(&self, data: &[u8], delimiter: u8, md5_length: usize) -> Result<JsValue, JsValue> {
+        check_md5_length(md5_length)?;
+        let consumed = data.iter().position(|&byte| byte == delimiter).unwrap_or(data.len());
+        let hex = self.truncate(&format!("{:x}", self.digest_async(&data[..consumed]).await), md5_length);
+        Ok(js_object(&[("hex", JsValue::from_str(&hex)), ("consumed", JsValue::from(consumed as f64))]))
+    }
+
+    /// MD5 of the sorted multiset difference `a - b`: both inputs must be sorted ascending,
+    /// and each byte of `b` cancels one equal byte of `a` (like `comm -23` on sorted lines).
+    /// The surviving bytes of `a` are hashed in order through a small buffer, without
+    /// building the difference. Rejects if either input turns out not to be sorted.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_diff_async(&self, a: &[u8], b: &[u8], md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        let unsorted = |name: &str| js_error(&format!("calculate_md5_diff_async input `{}` is not sorted", name));
+        if b.windows(2).any(|pair| pair[0] > pair[1]) {
+            return Err(unsorted("b"));
+        }
+
+        let mut hasher = Md5::new();
+        let mut pending = Vec::with_capacity(64 * 1024);
+        let mut failure = None;
+        let mut i = 0usize;
+        let mut j = 0;
+        let step = |max: usize| {
+            let start = i;
+            let end = a.len().min(start.saturating_add(max));
+            for (index, &byte) in a.iter().enumerate().take(end).skip(start) {
+                if index > 0 && a[index - 1] > byte {
+                    failure = Some(unsorted("a"));
+                    return 0;
+                }
+                while j < b.len() && b[j] < byte {
+                    j += 1;
+                }
+                if j < b.len() && b[j] == byte {
+                    j += 1;
+                } else {
+                    pending.push(byte);
+                    if pending.len() == pending.capacity() {
+                        hasher.update(&pending);
+                        self.count_hashed(pending.len() as u64);
+                        pending.clear();
+                    }
+                }
+            }
+            i = end;
+            end - start
+        };
+        self.feed_steps(a.len(), step, None, None).await;
+        if let Some(error) = failure {
+            return Err(error);
+        }
+        hasher.update(&pending);
+        self.count_hashed(pending.len() as u64);
+        Ok(self.truncate(&format!("{:x}", hasher.finalize()), md5_length))
+    }
+
+    /// MD5 of `data` zero-padded up to the next multiple of `block` bytes (no padding when
+    /// the length is already a multiple, including empty input). The zeros are streamed
+    /// into the hasher rather than allocated. Rejects a zero `block`.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_padded_async(&self, data: &[u8], block: usize, md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        if block == 0 {
+            return Err(js_error("block must be greater than zero"));
+        }
+
+        let mut hasher = Md5::new();
+        self.feed_chunked(data, |chunk| hasher.update(chunk)).await;
+        let mut padding = (block - data.len() % block) % block;
+        let zeros = [0u8; 4096];
+        while padding > 0 {
+            let len = padding.min(zeros.len());
+            hasher.update(&zeros[..len]);
+            padding -= len;
+        }
+        Ok(self.truncate(&format!("{:x}", hasher.finalize()), md5_length))
+    }
+
+    /// MD5 of `data` rotated left by `start`: `data[start..]` followed by `data[..start]`,
+    /// hashed in place without building the rotated copy. Rejects if `start > data.len()`.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_rotated_async(&self, data: &[u8], start: usize, md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        if start > data.len() {
+            return Err(js_error(&format!("Rotation start {} is out of range for {} bytes", start, data.len())));
+        }
+
+        let (head, tail) = data.split_at(start);
+        let mut hasher = Md5::new();
+        self.feed_chunked(tail, |chunk| hasher.update(chunk)).await;
+        self.feed_chunked(head, |chunk| hasher.update(chunk)).await;
+        Ok(self.truncate(&format!("{:x}", hasher.finalize()), md5_length))
+    }
+
+    /// MD5 of `data` in reverse byte order (last byte first). Walks the input backwards
+    /// through a 64 KB scratch buffer instead of building a reversed copy, yielding like the
+    /// other async methods.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_reversed_async(&self, data: &[u8], md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        let mut hasher = Md5::new();
+        let mut scratch = Vec::with_capacity(64 * 1024);
+        let len = data.len();
+        self.feed_ranges(
+            len,
+            |range| {
+                for chunk in data[len - range.end..len - range.start].rchunks(64 * 1024) {
+                    scratch.clear();
+                    scratch.extend(chunk.iter().rev());
+                    hasher.update(&scratch);
+                }
+            },
+            None,
+            None,
+        )
+        .await;
+
+        Ok(self.truncate(&format!("{:x}", hasher.finalize()), md5_length))
+    }
+
+    /// MD5 of `data` with the bit order of every byte reversed (`0x01` is hashed as `0x80`), as
+    /// some hardware checksum specs require. Bytes are mapped through a lookup table into a
+    /// 64 KB scratch buffer as they stream into the hasher; no transformed copy is built.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_bitreversed_async(&self, data: &[u8], md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        let mut hasher = Md5::new();
+        let mut scratch = Vec::with_capacity(64 * 1024);
+        self.feed_chunked(data, |chunk| {
+            for part in chunk.chunks(64 * 1024) {
+                scratch.clear();
+                scratch.extend(part.iter().map(|&byte| BIT_REVERSED[byte as usize]));
+                hasher.update(&scratch);
+            }
+        })
+        .await;
+
+        Ok(self.truncate(&format!("{:x}", hasher.finalize()), md5_length))
+    }
+
+    /// MD5 of 16-bit PCM samples in canonical big-endian order, so the same audio hashes alike
+    /// whichever byte order it was stored in. With `source_is_le` each sample is byte-swapped
+    /// through a 64 KB scratch buffer as it streams in; big-endian input is hashed as-is.
+    /// Rejects odd-length input, which is not a whole number of samples.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_pcm16_async(&self, data: &[u8], source_is_le: bool, md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        if !data.len().is_multiple_of(2) {
+            return Err(js_error(&format!("PCM16 input must have an even length, got {} bytes", data.len())));
+        }
+
+        let mut hasher = Md5::new();
+        if source_is_le {
+            let mut scratch = Vec::with_capacity(64 * 1024);
+            // Chunk sizes are even, so no sample straddles two chunks.
+            self.feed_chunked(data, |chunk| {
+                for part in chunk.chunks(64 * 1024) {
+                    scratch.clear();
+                    scratch.extend(part.chunks_exact(2).flat_map(|sample| [sample[1], sample[0]]));
+                    hasher.update(&scratch);
+                }
+            })
+            .await;
+        } else {
+            self.feed_chunked(data, |chunk| hasher.update(chunk)).await;
+        }
+        Ok(self.truncate(&format!("{:x}", hasher.finalize()), md5_length))
+    }
+
+    /// Compute MD5 and keep `md5_length` hex chars from the chosen `side` of the digest.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_truncated_async(&self, data: &[u8], md5_length: usize, side: TruncateSide) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        let hash_string = format!("{:x}", self.digest_async(data).await);
+        Ok(match side {
+            TruncateSide::Start => self.truncate(&hash_string, md5_length),
+            TruncateSide::End => {
+                let length = self.rounded_length(md5_length).min(hash_string.len());
+                hash_string[hash_string.len() - length..].to_string()
+            }
+        })
+    }
+
+    /// MD5 of every `window`-byte slice of `data` starting at offsets `0, step, 2 * step, ...`,
+    /// as `{ offset, hex }` objects in offset order (shingling for similarity detection).
+    /// A trailing window shorter than `window` is hashed only when `include_partial` is set;
+    /// input shorter than one window then yields a single partial shingle at offset 0.
+    /// Zero `window` or `step` rejects.
+    #[wasm_bindgen]
+    pub async fn calculate_shingles_async(
+        &self,
+        data: &[u8],
+        window: usize,
+        step: usize,
+        md5_length: usize,
+        include_partial: bool,
+    ) -> Result<Vec<JsValue>, JsValue> {
+        check_md5_length(md5_length)?;
+        if window == 0 || step == 0 {
+            return Err(js_error(&format!("window and step must be non-zero, got {} and {}", window, step)));
+        }
+
+        // The chunk loop advances over window start offsets; each window is hashed whole.
+        let len = data.len();
+        let mut shingles = Vec::new();
+        let mut offset = 0usize;
+        let mut finished = len == 0;
+        let advance = |max: usize| {
+            if finished {
+                return 0;
+            }
+            let start = offset;
+            let limit = start.saturating_add(max);
+            while !finished && offset < limit {
+                let end = offset.saturating_add(window);
+                if end > len && !include_partial {
+                    finished = true;
+                    break;
+                }
+                let slice = &data[offset..end.min(len)];
+                let hex = self.truncate(&format!("{:x}", Md5::digest(slice)), md5_length);
+                self.count_hashed(slice.len() as u64);
+                shingles.push(js_object(&[
+                    ("offset", JsValue::from(offset as f64)),
+                    ("hex", JsValue::from_str(&hex)),
+                ]));
+                offset = offset.saturating_add(step);
+                finished = end >= len || offset >= len;
+            }
+            if finished {
+                len - start
+            } else {
+                offset - start
+            }
+        };
+        self.feed_steps(len, advance, None, None).await;
+
+        console_log!(self.enable_log, "Shingle MD5 calculation completed: {} windows of {} bytes", shingles.len(), window);
+        Ok(shingles)
+    }
+
+    /// Hash a JS string as UTF-8 with explicit handling of lone surrogates, which the
+    /// implicit `&str` conversion silently replaces. With `replace_lone_surrogates` each lone
+    /// surrogate is encoded as U+FFFD (`EF BF BD`, the same bytes `TextEncoder` produces);
+    /// otherwise a string containing one rejects, so callers never disagree quietly.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_str_lossless_async(
+        &self,
+        text: js_sys::JsString,
+        md5_length: usize,
+        replace_lone_surrogates: bool,
+    ) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        let units: Vec<u16> = text.iter().collect();
+        let mut encoded = String::with_capacity(units.len());
+        for (index, decoded) in char::decode_utf16(units.iter().copied()).enumerate() {
+            match decoded {
+                Ok(c) => encoded.push(c),
+                Err(_) if replace_lone_surrogates => encoded.push(char::REPLACEMENT_CHARACTER),
+                Err(e) => {
+                    return Err(js_error(&format!(
+                        "String contains a lone surrogate 0x{:04X} near code point {}",
+                        e.unpaired_surrogate(),
+                        index
+                    )))
+                }
+            }
+        }
+
+        let truncated_hash = self.truncate(&format!("{:x}", self.digest_async(encoded.as_bytes()).await), md5_length);
+        console_log!(self.enable_log, "Lossless string MD5 calculation completed: {}", truncated_hash);
+        Ok(truncated_hash)
+    }
+
+    /// Full digest as a fixed-width, lexicographically sortable key: the 16 bytes in
+    /// unpadded RFC 4648 base32hex (`0-9A-V`), always 26 chars. Keys sort in the same order as
+    /// the raw digest bytes. This format is stable across versions.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_sortkey_async(&self, data: &[u8]) -> String {
+        const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHIJKLMNOPQRSTUV";
+        let digest = self.digest_async(data).await;
+        let mut key = String::with_capacity(26);
+        let mut buffer = 0u32;
+        let mut bits = 0;
+        for &byte in digest.iter() {
+            buffer = (buffer << 8) | u32::from(byte);
+            bits += 8;
+            while bits >= 5 {
+                bits -= 5;
+                key.push(ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+            }
+        }
+        // 128 bits leave 3 over: the last group is zero-padded on the right.
+        key.push(ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+        key
+    }
+
+    /// Compute the digest and store `{ key, hex }` in the object store `store` names. Hashing
+    /// yields (and may be paused), which lets the transaction `store` came from commit, so the
+    /// write goes through a new `readwrite` transaction on the same database once the digest
+    /// is ready. Stores with a key path take the object as-is; otherwise `key` is also passed
+    /// as the out-of-line key. The digest is returned even if the write fails; the failure is
+    /// reported with `console.warn` (regardless of the log setting).
+    #[wasm_bindgen]
+    pub async fn calculate_md5_and_cache_async(
+        &self,
+        key: &str,
+        data: &[u8],
+        store: web_sys::IdbObjectStore,
+        md5_length: usize,
+    ) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        let database = store.transaction().db();
+        let store_name = store.name();
+        let in_line_keys = store.key_path().map(|path| !path.is_null()).unwrap_or(false);
+        let hex = self.truncate(&format!("{:x}", self.digest_async(data).await), md5_length);
+        let record = js_object(&[("key", JsValue::from_str(key)), ("hex", JsValue::from_str(&hex))]);
+
+        let request = database
+            .transaction_with_str_and_mode(&store_name, web_sys::IdbTransactionMode::Readwrite)
+            .and_then(|transaction| transaction.object_store(&store_name))
+            .and_then(|store| if in_line_keys { store.put(&record) } else { store.put_with_key(&record, &JsValue::from_str(key)) });
+        let written = match request {
+            Ok(request) => idb_request_done(&request).await,
+            Err(error) => Err(error),
+        };
+        match written {
+            Ok(_) => console_log!(self.enable_log, "Cached MD5 for {}: {}", key, hex),
+            Err(error) => web_sys::console::warn_2(&JsValue::from_str(&format!("Failed to cache MD5 for {}:", key)), &error),
+        }
+        Ok(hex)
+    }
+
+    /// Hash a `Blob` (or `File`) by reading it `chunk_size` bytes at a time, yielding between
+    /// chunks. Failed chunk reads are retried per `set_read_retries`; once retries run out,
+    /// or for a zero `chunk_size`, the promise rejects with the last read error.
+    #[wasm_bindgen]
+    pub async fn hash_blob_async(&self, blob: web_sys::Blob, chunk_size: usize, md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        if chunk_size == 0 {
+            return Err(js_error("chunk_size must be greater than zero"));
+        }
+
+        let total = blob.size();
+        let mut hasher = Md5::new();
+        let mut offset = 0.0;
+        while offset < total {
+            let end = total.min(offset + chunk_size as f64);
+            let mut attempt = 0;
+            let chunk = loop {
+                match read_blob_range(&blob, offset, end).await {
+                    Ok(chunk) => break chunk,
+                    Err(_) if attempt < self.read_retries => {
+                        attempt += 1;
+                        console_log!(self.enable_log, "WARNING: Blob read at {} failed, retry {}/{}", offset, attempt, self.read_retries);
+                        sleep_ms(50 * attempt as i32).await;
+                    }
+                    Err(error) => return Err(error),
+                }
+            };
+            hasher.update(&chunk);
+            self.count_hashed(chunk.len() as u64);
+            offset = end;
+            self.yield_point().await;
+        }
+
+        let hex = self.truncate(&format!("{:x}", hasher.finalize()), md5_length);
+        console_log!(self.enable_log, "Blob MD5 calculation completed: {}, bytes: {}", hex, total);
+        Ok(hex)
+    }
+
+    /// Hash `data` and upload it with `fetch(url, init)`, sending `data` as the body and its
+    /// base64 MD5 as the `Content-MD5` header, so the two cannot drift apart. `init` is an
+    /// optional `RequestInit`-like object; its `body` is replaced, an existing `Content-MD5`
+    /// header is overwritten, and `method` defaults to `PUT`. Resolves with the `Response`
+    /// whatever its status; rejects if the request cannot be built or `fetch` fails.
+    #[wasm_bindgen]
+    pub async fn hash_and_put_async(&self, url: &str, data: &[u8], init: JsValue) -> Result<web_sys::Response, JsValue> {
+        let content_md5 = encode_base64(&self.digest_async(data).await);
+
+        let options = js_sys::Object::new();
+        if init.is_object() {
+            js_sys::Object::assign(&options, init.unchecked_ref());
+        }
+        let options: web_sys::RequestInit = options.unchecked_into();
+        if options.get_method().is_none() {
+            options.set_method("PUT");
+        }
+        options.set_body(&js_sys::Uint8Array::from(data));
+
+        let request = web_sys::Request::new_with_str_and_init(url, &options)?;
+        request.headers().set("Content-MD5", &content_md5)?;
+        let fetch = js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("fetch"))?
+            .dyn_into::<js_sys::Function>()
+            .map_err(|_| js_error("fetch is not available in this context"))?;
+        let response = fetch.call1(&JsValue::NULL, &request)?;
+        let response = wasm_bindgen_futures::JsFuture::from(js_sys::Promise::resolve(&response)).await?;
+
+        console_log!(self.enable_log, "Uploaded {} bytes to {} with Content-MD5 {}", data.len(), url, content_md5);
+        Ok(response.unchecked_into())
+    }
+
+    /// Hash a `Blob` or `File` through its `stream()`, so the content never has to fit in a
+    /// single `Uint8Array`; see `calculate_md5_stream_async`.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_blob_async(&self, blob: web_sys::Blob, md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        self.calculate_md5_stream_async(blob.stream(), md5_length).await
+    }
+
+    /// Hash a `ReadableStream` of `Uint8Array` chunks read one at a time with `getReader()`:
+    /// only the current chunk is held in WASM memory, and the loop yields to the event loop
+    /// every 2 MB read, like the buffer-based methods. Resolves with the same hex as
+    /// `calculate_md5_async` over the concatenated chunks; stream errors and non-`Uint8Array`
+    /// chunks reject (the reader is cancelled in the latter case).
+    #[wasm_bindgen]
+    pub async fn calculate_md5_stream_async(&self, stream: web_sys::ReadableStream, md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        let mut hasher = Md5::new();
+        let total = self
+            .read_stream(&stream, |chunk| {
+                hasher.update(chunk);
+                Ok(())
+            })
+            .await?;
+        self.count_hashed(total);
+
+        let hex = self.truncate(&format!("{:x}", hasher.finalize()), md5_length);
+        console_log!(self.enable_log, "Stream MD5 calculation completed: {}, bytes: {}", hex, total);
+        Ok(hex)
+    }
+
+    /// Compute MD5 and render it as a short human-comparable fingerprint, e.g.
+    /// `able-tiger-north-42`. The word mapping is fixed and stable across versions.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_fingerprint_async(&self, data: &[u8]) -> String {
+        fingerprint::fingerprint(&self.digest_async(data).await)
+    }
+
+    /// RFC 4122 name-based (version 3) UUID: MD5 of the 16 namespace bytes followed by `name`,
+    /// with the version nibble set to 3 and the RFC 4122 variant bits, in canonical lowercase
+    /// `8-4-4-4-12` form. `namespace` is a UUID string, with or without hyphens; anything that
+    /// does not decode to 16 bytes rejects.
+    #[wasm_bindgen]
+    pub async fn calculate_uuid_v3_async(&self, namespace: &str, name: &[u8]) -> Result<String, JsValue> {
+        let namespace_bytes = decode_hex(&namespace.trim().replace('-', ""))
+            .filter(|bytes| bytes.len() == 16)
+            .ok_or_else(|| js_error(&format!("Invalid namespace UUID: {}", namespace)))?;
+
+        let mut input = namespace_bytes;
+        input.extend_from_slice(name);
+        let mut bytes = self.digest_async(&input).await;
+        bytes[6] = (bytes[6] & 0x0f) | 0x30;
+        bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+        let hex = format!("{:x}", bytes);
+        Ok(format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..]))
+    }
+
+    /// Consistent-hashing bucket for `key`: the first 8 digest bytes read as a big-endian
+    /// `u64`, modulo `buckets`. Matches Python's `int(md5(key).hexdigest()[:16], 16) % buckets`.
+    /// Zero buckets rejects.
+    #[wasm_bindgen]
+    pub async fn md5_bucket_async(&self, key: &[u8], buckets: u32) -> Result<u32, JsValue> {
+        self.md5_bucket_endian_async(key, buckets, Endian::Big).await
+    }
+
+    /// `md5_bucket_async` with the byte order of the 8-byte prefix chosen by `endian`.
+    /// `Endian::Big` is `md5_bucket_async`; `Endian::Little` matches servers that read the
+    /// prefix as a native little-endian integer.
+    #[wasm_bindgen]
+    pub async fn md5_bucket_endian_async(&self, key: &[u8], buckets: u32, endian: Endian) -> Result<u32, JsValue> {
+        if buckets == 0 {
+            return Err(js_error("buckets must be greater than zero"));
+        }
+        let [prefix, _] = digest_u64x2(&self.digest_async(key).await, endian);
+        Ok((prefix % u64::from(buckets)) as u32)
+    }
+
+    /// The digest as two `u64`s (bytes 0..8 and 8..16, in that order), each read in
+    /// `endian` byte order. With `Endian::Big` the pair concatenated in hex is the hex digest.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_u64x2_async(&self, data: &[u8], endian: Endian) -> Vec<u64> {
+        digest_u64x2(&self.digest_async(data).await, endian).to_vec()
+    }
+
+    /// Compare the MD5 of `data` against `expected_hex` (case-insensitive, trimmed) and
+    /// return `{ match, computed, expected, first_diff_nibble }` for debugging. `match` is
+    /// decided with a constant-time comparison; only after a mismatch is the index of the
+    /// first differing hex character computed (-1 when they match).
+    #[wasm_bindgen]
+    pub fn compare_md5(&self, data: &[u8], expected_hex: &str) -> JsValue {
+        self.count_hashed(data.len() as u64);
+        let computed = format!("{:x}", Md5::digest(data));
+        let expected = expected_hex.trim().to_ascii_lowercase();
+        let matched = constant_time_eq(computed.as_bytes(), expected.as_bytes());
+
+        let first_diff = if matched {
+            -1.0
+        } else {
+            computed
+                .bytes()
+                .zip(expected.bytes())
+                .position(|(a, b)| a != b)
+                .unwrap_or_else(|| computed.len().min(expected.len())) as f64
+        };
+        console_log!(self.enable_log, "MD5 comparison: computed {}, expected {}, match: {}", computed, expected, matched);
+
+        js_object(&[
+            ("match", JsValue::from_bool(matched)),
+            ("computed", JsValue::from_str(&computed)),
+            ("expected", JsValue::from_str(&expected)),
+            ("first_diff_nibble", JsValue::from_f64(first_diff)),
+        ])
+    }
+
+    /// Hash everything produced by a JS async iterable (anything usable with `for await`),
+    /// or an async iterator object itself. Each yielded value must be a `Uint8Array`;
+    /// anything else rejects with a `TypeError`, as do errors thrown by the iterator.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_async_iter_async(&self, iterable: JsValue, md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        let factory = js_sys::Reflect::get(&iterable, &js_sys::Symbol::async_iterator())?;
+        let iterator = match factory.dyn_ref::<js_sys::Function>() {
+            Some(factory) => factory.call0(&iterable)?,
+            None => iterable,
+        };
+        let next = js_sys::Reflect::get(&iterator, &JsValue::from_str("next"))?
+            .dyn_into::<js_sys::Function>()
+            .map_err(|_| JsValue::from(js_sys::TypeError::new("Value is not an async iterable")))?;
+
+        let mut hasher = Md5::new();
+        let mut total = 0usize;
+        loop {
+            let step = wasm_bindgen_futures::JsFuture::from(js_sys::Promise::resolve(&next.call0(&iterator)?)).await?;
+            if js_sys::Reflect::get(&step, &JsValue::from_str("done"))?.is_truthy() {
+                break;
+            }
+            let chunk = js_sys::Reflect::get(&step, &JsValue::from_str("value"))?
+                .dyn_into::<js_sys::Uint8Array>()
+                .map_err(|_| JsValue::from(js_sys::TypeError::new("Async iterator yielded a non-Uint8Array value")))?
+                .to_vec();
+            hasher.update(&chunk);
+            self.count_hashed(chunk.len() as u64);
+            total += chunk.len();
+        }
+
+        let truncated_hash = self.truncate(&format!("{:x}", hasher.finalize()), md5_length);
+        console_log!(self.enable_log, "Async iterator MD5 calculation completed: {}, bytes: {}", truncated_hash, total);
+        Ok(truncated_hash)
+    }
+
+    /// Hash the canonical JSON form of `value`: object keys sorted by code point, compact
+    /// separators, integral numbers without a fraction. Values JSON cannot represent
+    /// (functions, symbols, BigInt, cycles) reject instead of being silently dropped, and so
+    /// does `undefined` anywhere in `value` (at the top level, as a property or as an array
+    /// element), which `JSON.stringify` would drop or turn into `null`.
+    #[wasm_bindgen]
+    pub async fn calculate_md5_json_async(&self, value: JsValue, md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        // JSON.stringify throws on cycles and BigInt before serde would recurse forever.
+        js_sys::JSON::stringify(&value)?;
+        // serde_wasm_bindgen would read `undefined` as `null`.
+        if contains_undefined(&value) {
+            return Err(js_error("Value is not JSON-serializable: it contains undefined"));
+        }
+        let parsed: serde_json::Value = serde_wasm_bindgen::from_value(value)
+            .map_err(|e| js_error(&format!("Value is not JSON-serializable: {}", e)))?;
+        // serde_json's default map is a BTreeMap, so serialization emits sorted keys.
+        let canonical = serde_json::to_string(&parsed).map_err(|e| js_error(&e.to_string()))?;
+
+        let truncated_hash = self.truncate(&format!("{:x}", self.digest_async(canonical.as_bytes()).await), md5_length);
+        console_log!(self.enable_log, "Canonical JSON MD5 calculation completed: {}, json length: {}", truncated_hash, canonical.len());
+        Ok(truncated_hash)
+    }
+
+    #[wasm_bindgen]
+    pub fn set_log_enabled(&mut self, enable: bool) {
+        self.enable_log = enable;
+    }
+
+    #[wasm_bindgen]
+    pub fn is_log_enabled(&self) -> bool {
+        self.enable_log
+    }
+
+    /// Also yield whenever more than `ms` milliseconds (per `performance.now()`) have passed
+    /// since the last yield, in addition to the byte-based cadence. `0` disables (default).
+    #[wasm_bindgen]
+    pub fn set_max_block_ms(&mut self, ms: f64) {
+        self.max_block_ms = if ms.is_finite() && ms > 0.0 { ms } else { 0.0 };
+    }
+
+    /// Feed large inputs to the hasher in chunks of `bytes` instead of the size-based default
+    /// (128KB, or 256KB above 10MB). Inputs of 512KB or less are still hashed in one call.
+    #[wasm_bindgen]
+    pub fn set_chunk_size(&mut self, bytes: usize) -> Result<(), JsValue> {
+        if bytes == 0 {
+            return Err(js_error("chunk size must be greater than zero"));
+        }
+        self.chunk_size = Some(bytes);
+        Ok(())
+    }
+
+    /// Yield to the event loop after every `bytes` hashed (default 2MB). Smaller values keep
+    /// the page more responsive on slow devices at some cost in throughput.
+    #[wasm_bindgen]
+    pub fn set_yield_interval(&mut self, bytes: usize) -> Result<(), JsValue> {
+        if bytes == 0 {
+            return Err(js_error("yield interval must be greater than zero"));
+        }
+        self.yield_interval = bytes;
+        Ok(())
+    }
+
+    /// When disabled, the async methods never yield to the event loop: the whole input is
+    /// hashed in one synchronous burst and the returned promise is already settled. `pause`
+    /// has no effect while yielding is disabled.
+    /// Enabled by default; intended for benchmarks and latency-sensitive small inputs.
+    #[wasm_bindgen]
+    pub fn set_yielding_enabled(&mut self, enabled: bool) {
+        self.yielding_enabled = enabled;
+    }
+
+    /// Yield through `scheduler.postTask` at `priority` (`"user-blocking"`, `"user-visible"` or
+    /// `"background"`) where the Scheduler API exists, falling back to the default microtask
+    /// yield elsewhere. An empty string restores the default; any other value throws.
+    #[wasm_bindgen]
+    pub fn set_scheduler_priority(&mut self, priority: &str) -> Result<(), JsValue> {
+        self.scheduler_priority = match priority {
+            "" => None,
+            "user-blocking" | "user-visible" | "background" => Some(priority.to_string()),
+            _ => return Err(js_error(&format!("Unknown scheduler priority: {}", priority))),
+        };
+        Ok(())
+    }
+
+    /// Choose how `md5_length` values that are not 16 or 32 are rounded before truncating,
+    /// for every method on this instance. Defaults to `LengthRounding::Exact`.
+    #[wasm_bindgen]
+    pub fn set_length_rounding(&mut self, rounding: LengthRounding) {
+        self.length_rounding = rounding;
+    }
+
+    /// Retry a failed chunk read in `hash_blob_async` up to `attempts` more times, waiting
+    /// 50 ms times the attempt number before each retry, before the hash rejects. Defaults
+    /// to 0 (fail on the first error).
+    #[wasm_bindgen]
+    pub fn set_read_retries(&mut self, attempts: usize) {
+        self.read_retries = attempts;
+    }
+
+    /// Keep the digests of the last `n` distinct inputs to `calculate_md5_async` and answer a
+    /// repeated input from the cache instead of rehashing it. Candidates are found by length
+    /// and a CRC-32 of their first and last 64 bytes, then confirmed by comparing the full
+    /// input, so a hit is always correct. Each entry holds a copy of its input, so keep `n`
+    /// small for large buffers. 0 (the default) disables and clears the cache.
+    #[wasm_bindgen]
+    pub fn set_cache_capacity(&mut self, n: usize) {
+        self.digest_cache.get_mut().set_capacity(n);
+        console_log!(self.enable_log, "Digest cache capacity set to {}", n);
+    }
+
+    /// In strict mode, `update_incremental_md5` with an empty slice returns false instead of
+    /// silently succeeding, to surface chunkers that emit empty reads. Off by default.
+    #[wasm_bindgen]
+    pub fn set_strict_empty_updates(&mut self, strict: bool) {
+        self.strict_empty_updates = strict;
+    }
+
+    /// Debug aid: while on, every `update_incremental_md5` call records its length in the
+    /// session, for `session_update_sizes`. Off by default, so updates pay nothing extra.
+    #[wasm_bindgen]
+    pub fn set_track_update_sizes(&mut self, enabled: bool) {
+        self.track_update_sizes = enabled;
+    }
+
+    /// Throughput of the most recent `calculate_md5_async` (or `calculate_md5_from_js_async`) call, timed with
+    /// `performance.now()`: `{ bytesHashed, elapsedMs, mbPerSecond }`. All zero before the
+    /// first call and for an aborted one; `mbPerSecond` is 0 when no time was measured.
+    #[wasm_bindgen]
+    pub fn last_stats(&self) -> JsValue {
+        let (bytes, elapsed_ms) = self.last_stats.get();
+        js_object(&[
+            ("bytesHashed", JsValue::from(bytes as f64)),
+            ("elapsedMs", JsValue::from(elapsed_ms)),
+            ("mbPerSecond", JsValue::from(mb_per_second(bytes, elapsed_ms))),
+        ])
+    }
+
+    /// WASM linear memory size in bytes (page-granular, 64 KiB pages) when the most recent
+    /// `calculate_md5_async` (or `calculate_md5_from_js_async`) call finished; 0 before any call. Linear memory never shrinks, so
+    /// this is the high-water mark up to and including that call. Always 0 off wasm32.
+    #[wasm_bindgen]
+    pub fn last_hash_peak_memory_bytes(&self) -> usize {
+        self.last_peak_memory.get()
+    }
+
+    /// Cumulative bytes this instance has hashed across one-shot, batch and incremental calls
+    /// since it was constructed. Monotonic: `reset` does not clear it.
+    #[wasm_bindgen]
+    pub fn total_bytes_hashed(&self) -> u64 {
+        self.bytes_hashed.get()
+    }
+
+    /// Pause every in-flight async hash on this calculator at its next yield point until
+    /// `resume` is called. Takes `&self` so it can be called while those calls are running.
+    /// Yield points are the only place a pause takes effect, and with
+    /// `set_yielding_enabled(false)` no method has any, so every method then runs to
+    /// completion regardless of `pause`.
+    #[wasm_bindgen]
+    pub fn pause(&self) {
+        self.paused.set(true);
+        console_log!(self.enable_log, "MD5 hashing paused");
+    }
+
+    /// Let paused async hashes continue from where they stopped.
+    #[wasm_bindgen]
+    pub fn resume(&self) {
+        self.paused.set(false);
+        for resolve in self.resume_waiters.take() {
+            let _ = resolve.call0(&JsValue::NULL);
+        }
+        console_log!(self.enable_log, "MD5 hashing resumed");
+    }
+
+    /// Whether `pause` is currently in effect.
+    #[wasm_bindgen]
+    pub fn is_paused(&self) -> bool {
+        self.paused.get()
+    }
+
+    /// Restore constructor defaults (logging, chunk and yield policy, pause state) and drop
+    /// every incremental (MD5 and SHA-256) and rolling session this instance owns; other
+    /// instances' sessions are left alone. The instance that last started an id owns it, so
+    /// an id another instance has since restarted survives. The instance stream (`update`)
+    /// is discarded; `total_bytes_hashed` is kept.
+    #[wasm_bindgen]
+    pub fn reset(&mut self) {
+        let dropped = self.drop_owned_sessions();
+        console_log!(self.enable_log, "Reset calculator, dropped {} sessions", dropped);
+        let bytes_hashed = self.bytes_hashed.get();
+        *self = Md5Calculator::new();
+        self.bytes_hashed.set(bytes_hashed);
+    }
+
+    /// Append `data` to this instance's own stream: the single-stream alternative to an
+    /// incremental session, with no session id and no global map lookup.
+    #[wasm_bindgen]
+    pub fn update(&mut self, data: &[u8]) {
+        self.hasher.update(data);
+        self.count_hashed(data.len() as u64);
+    }
+
+    /// Digest of everything passed to `update` since the last `digest` (or construction, or
+    /// `reset`), truncated to `md5_length`. The stream restarts empty, ready for the next input.
+    #[wasm_bindgen]
+    pub fn digest(&mut self, md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        let hex = format!("{:x}", self.hasher.finalize_reset());
+        Ok(self.truncate(&hex, md5_length))
+    }
+
+    /// Begin an incremental (streaming) MD5 session identified by `session_id`. Returns true
+    /// if this replaced a live session with the same id, whose state is discarded. At the
+    /// `set_max_sessions` limit a new id is not started: this and every other session
+    /// starter throw `{ code: "session_limit", session_id }`.
+    #[wasm_bindgen]
+    pub fn start_incremental_md5(&self, session_id: &str) -> Result<bool, JsValue> {
+        self.check_session_limit(&HASH_STATES, session_id)?;
+        let clobbered = self.insert_session(session_id, Session::new()).is_some();
+        if clobbered {
+            console_log!(self.enable_log, "WARNING: Restarted live incremental MD5 session: {}", session_id);
+        } else {
+            console_log!(self.enable_log, "Started incremental MD5 session: {}", session_id);
+        }
+        Ok(clobbered)
+    }
+
+    /// Limit on live incremental sessions (from any instance) beyond which this instance's
+    /// start and import methods refuse new ids; restarting a live id is always allowed.
+    /// Rolling and SHA-256 sessions are limited separately, each to the same number.
+    /// Defaults to 1024; zero is rejected.
+    #[wasm_bindgen]
+    pub fn set_max_sessions(&mut self, max_sessions: usize) -> Result<(), JsValue> {
+        if max_sessions == 0 {
+            return Err(js_error("max_sessions must be greater than zero"));
+        }
+        self.max_sessions = max_sessions;
+        Ok(())
+    }
+
+    /// Number of live incremental sessions, from any instance.
+    #[wasm_bindgen]
+    pub fn session_count(&self) -> usize {
+        HASH_STATES.with(|states| states.borrow().len())
+    }
+
+    /// Ids of every live incremental session, from any instance, sorted; for auditing sessions
+    /// that were never finalized or cancelled.
+    #[wasm_bindgen]
+    pub fn list_active_sessions(&self) -> Vec<String> {
+        let mut ids: Vec<String> = HASH_STATES.with(|states| states.borrow().keys().cloned().collect());
+        ids.sort();
+        ids
+    }
+
+    /// Drop every live incremental session, from any instance, to reclaim their memory.
+    /// Ownership goes with the sessions, so ids started afterwards belong to whoever starts
+    /// them. Rolling and SHA-256 sessions are not affected. Returns how many were dropped.
+    #[wasm_bindgen]
+    pub fn clear_all_sessions(&self) -> usize {
+        let cleared = HASH_STATES.with(|states| std::mem::take(&mut *states.borrow_mut()));
+        console_log!(self.enable_log, "Cleared {} incremental MD5 sessions", cleared.len());
+        cleared.len()
+    }
+
+    /// Begin an incremental session that has already consumed `salt`, so content passed to
+    /// later updates is always hashed as `salt || content`.
+    #[wasm_bindgen]
+    pub fn start_incremental_md5_salted(&self, session_id: &str, salt: &[u8]) -> Result<(), JsValue> {
+        self.check_session_limit(&HASH_STATES, session_id)?;
+        let mut session = Session::new();
+        session.update(salt);
+        self.insert_session(session_id, session);
+        console_log!(self.enable_log, "Started salted incremental MD5 session: {}, salt length: {}", session_id, salt.len());
+        Ok(())
+    }
+
+    /// Begin a domain-separated incremental session. The hasher is seeded with the framed tag
+    /// `len(domain) || domain`: the UTF-8 byte length of `domain` as a u64 big-endian, then
+    /// those bytes. The length prefix keeps different domains from producing colliding
+    /// digests for the same content (e.g. domain `ab` + data `c` vs domain `a` + data `bc`).
+    #[wasm_bindgen]
+    pub fn start_incremental_md5_domain(&self, session_id: &str, domain: &str) -> Result<(), JsValue> {
+        self.check_session_limit(&HASH_STATES, session_id)?;
+        let mut session = Session::new();
+        session.update(&(domain.len() as u64).to_be_bytes());
+        session.update(domain.as_bytes());
+        self.insert_session(session_id, session);
+        console_log!(self.enable_log, "Started domain-separated incremental MD5 session: {}, domain: {}", session_id, domain);
+        Ok(())
+    }
+
+    /// Begin an incremental session whose hasher has already consumed `seed_data`, e.g. a
+    /// header, so later updates continue from that state. Equivalent to start + update.
+    #[wasm_bindgen]
+    pub fn start_incremental_md5_from(&self, session_id: &str, seed_data: &[u8]) -> Result<(), JsValue> {
+        self.check_session_limit(&HASH_STATES, session_id)?;
+        let mut session = Session::new();
+        session.update(seed_data);
+        self.insert_session(session_id, session);
+        console_log!(self.enable_log, "Started seeded incremental MD5 session: {}, seed length: {}", session_id, seed_data.len());
+        Ok(())
+    }
+
+    /// Begin an incremental session carrying arbitrary caller `meta` (e.g. filename and
+    /// expected size). The crate never inspects it; read it back with `get_session_meta`.
+    #[wasm_bindgen]
+    pub fn start_incremental_md5_with_meta(&self, session_id: &str, meta: JsValue) -> Result<(), JsValue> {
+        self.check_session_limit(&HASH_STATES, session_id)?;
+        let mut session = Session::new();
+        session.meta = meta;
+        self.insert_session(session_id, session);
+        console_log!(self.enable_log, "Started incremental MD5 session with metadata: {}", session_id);
+        Ok(())
+    }
+
+    /// Begin an incremental session that auto-finalizes once it has consumed `max_bytes`:
+    /// the update reaching the cap hashes only the bytes up to it, then the session is
+    /// finalized and `on_complete(hex)` is called with the full hex digest (followed by any
+    /// `set_on_finalize` callback). Later updates find no session and return false, as after
+    /// `finalize_incremental_md5`. A zero cap completes on the first update.
+    #[wasm_bindgen]
+    pub fn start_incremental_md5_capped(&self, session_id: &str, max_bytes: u64, on_complete: js_sys::Function) -> Result<(), JsValue> {
+        self.check_session_limit(&HASH_STATES, session_id)?;
+        let mut session = Session::new();
+        session.cap = Some(ByteCap { max_bytes, on_complete });
+        self.insert_session(session_id, session);
+        console_log!(self.enable_log, "Started capped incremental MD5 session: {}, max bytes: {}", session_id, max_bytes);
+        Ok(())
+    }
+
+    /// Metadata attached to a live session, or `undefined` if the session does not exist
+    /// or was started without any.
+    #[wasm_bindgen]
+    pub fn get_session_meta(&self, session_id: &str) -> JsValue {
+        HASH_STATES.with(|states| {
+            states
+                .borrow()
+                .get(session_id)
+                .map_or(JsValue::UNDEFINED, |session| session.meta.clone())
+        })
+    }
+
+    /// Feed data into an active incremental session. Returns true on success.
+    /// Empty updates are rejected (false) when strict empty updates are enabled. A capped
+    /// session (`start_incremental_md5_capped`) hashes no more than its cap.
+    #[wasm_bindgen]
+    pub fn update_incremental_md5(&self, session_id: &str, data: &[u8]) -> bool {
+        if data.is_empty() && self.strict_empty_updates {
+            console_log!(self.enable_log, "WARNING: Rejected empty update for incremental MD5 session: {}", session_id);
+            return false;
+        }
+        let updated = self.apply_update(session_id, data);
+        self.finish_update(session_id, updated)
+    }
+
+    /// Feed one large update into a session `chunk_size` bytes at a time, yielding between
+    /// chunks so the main thread stays responsive; the result matches a single
+    /// `update_incremental_md5` call. Rejects for a missing session (also if it disappears
+    /// between chunks) or a zero `chunk_size`; chunks fed before that remain in the session.
+    #[wasm_bindgen]
+    pub async fn update_incremental_md5_chunked_async(&self, session_id: &str, data: &[u8], chunk_size: usize) -> Result<bool, JsValue> {
+        if chunk_size == 0 {
+            return Err(js_error("chunk_size must be greater than zero"));
+        }
+        let missing = || js_error(&format!("Incremental MD5 session not found: {}", session_id));
+        if !HASH_STATES.with(|states| states.borrow().contains_key(session_id)) {
+            return Err(missing());
+        }
+        if data.is_empty() {
+            return Ok(self.update_incremental_md5(session_id, data));
+        }
+
+        for (index, chunk) in data.chunks(chunk_size).enumerate() {
+            if index > 0 {
+                self.yield_point().await;
+            }
+            if !self.update_incremental_md5(session_id, chunk) {
+                return Err(missing());
+            }
+        }
+        Ok(true)
+    }
+
+    /// Variant of `update_incremental_md5` for hot streaming paths: `view` must be a
+    /// `Uint8Array` over this module's own linear memory (e.g. created with `Uint8Array::view`
+    /// or from `memory.buffer`), and its bytes are hashed where they lie, without a copy.
+    /// Throws for a view over any other buffer (including one detached by memory growth) or
+    /// one reaching past the end of linear memory.
+    #[wasm_bindgen]
+    pub fn update_incremental_md5_view(&self, session_id: &str, view: &js_sys::Uint8Array) -> Result<bool, JsValue> {
+        let memory_buffer = wasm_bindgen::memory().unchecked_into::<js_sys::WebAssembly::Memory>().buffer();
+        if !js_sys::Object::is(&view.buffer(), &memory_buffer) {
+            return Err(js_error("update_incremental_md5_view requires a view over WASM linear memory"));
+        }
+        let start = view.byte_offset() as usize;
+        let len = view.length() as usize;
+        let memory_len = memory_buffer.unchecked_into::<js_sys::ArrayBuffer>().byte_length() as usize;
+        if start.checked_add(len).is_none_or(|end| end > memory_len) {
+            return Err(js_error("Uint8Array view extends past the end of WASM linear memory"));
+        }
+        if len == 0 || start == 0 {
+            // Nothing to borrow, or a view at address 0, which a slice can't point to.
+            return Ok(self.update_incremental_md5(session_id, &view.to_vec()));
+        }
+
+        let updated = {
+            // SAFETY: `start..start + len` was checked above to lie inside linear memory, whose
+            // non-null addresses stay valid as it grows (it never shrinks). The slice is
+            // only read, and it is dropped at the end of this block: `apply_update` runs no JS,
+            // so nothing can write to those bytes while it is alive. Callbacks fire afterwards.
+            let data = unsafe { std::slice::from_raw_parts(start as *const u8, len) };
+            self.apply_update(session_id, data)
+        };
+        Ok(self.finish_update(session_id, updated))
+    }
+
+    /// Hash `data` into the session and collect the callbacks it makes due, without running
+    /// any JS. Split from `finish_update` so callers hashing borrowed linear memory can end
+    /// the borrow before callbacks get a chance to write to it.
+    fn apply_update(&self, session_id: &str, data: &[u8]) -> Option<AppliedUpdate> {
+        utils::with_active_session(session_id, || HASH_STATES.with(|states| {
+            let mut map = states.borrow_mut();
+            let session = map.get_mut(session_id)?;
+            let data = match &session.cap {
+                Some(cap) => &data[..(data.len() as u64).min(cap.max_bytes - session.update_bytes) as usize],
+                None => data,
+            };
+            let started = now_ms();
+            session.update(data);
+            session.update_ms += now_ms() - started;
+            session.update_bytes += data.len() as u64;
+            if self.track_update_sizes {
+                session.update_sizes.push(data.len());
+            }
+
+            let mut callbacks = Vec::new();
+            #[cfg(feature = "session-state")]
+            callbacks.extend(session.due_checkpoint());
+            callbacks.extend(session.due_interval_digest());
+
+            let mut capped = None;
+            if session.cap.as_ref().is_some_and(|cap| session.update_bytes >= cap.max_bytes) {
+                let session = map.remove(session_id)?;
+                let hex = format!("{:x}", session.hasher.finalize());
+                if let Some(cap) = session.cap {
+                    callbacks.push((cap.on_complete, js_sys::Array::of1(&JsValue::from_str(&hex))));
+                }
+                capped = Some(hex);
+            }
+            Some((data.len(), callbacks, capped))
+        }))
+    }
+
+    /// Count, log and fire the callbacks of an `apply_update`; false if the session was missing.
+    fn finish_update(&self, session_id: &str, updated: Option<AppliedUpdate>) -> bool {
+        match updated {
+            Some((len, callbacks, capped)) => {
+                self.count_hashed(len as u64);
+                console_log!(self.enable_log, "Updated incremental MD5 session: {}, data length: {}", session_id, len);
+                if let Some(hex) = &capped {
+                    console_log!(self.enable_log, "Capped incremental MD5 session reached its limit: {}, result: {}", session_id, hex);
+                    remember_finalized(session_id);
+                }
+                fire_callbacks(callbacks);
+                if let Some(hex) = capped {
+                    self.notify_finalized(session_id, &hex);
+                }
+                true
+            }
+            None => {
+                console_log!(self.enable_log, "Incremental MD5 session not found: {}", session_id);
+                false
+            }
+        }
+    }
+
+    /// Feed the UTF-8 bytes of `text` into the session, e.g. chunks from a `TextDecoder`
+    /// stream. Same result and return value as `update_incremental_md5` on those bytes.
+    #[wasm_bindgen]
+    pub fn update_incremental_md5_str(&self, session_id: &str, text: &str) -> bool {
+        self.update_incremental_md5(session_id, text.as_bytes())
+    }
+
+    /// Encode `text` as `encoding` (`"utf-8"` or `"latin1"`, case-insensitive) and feed the
+    /// bytes into the session, with the same result as `update_incremental_md5`. Throws for an
+    /// unknown encoding, or for latin1 text containing characters above U+00FF.
+    #[wasm_bindgen]
+    pub fn update_incremental_md5_text(&self, session_id: &str, text: &str, encoding: &str) -> Result<bool, JsValue> {
+        let bytes = encode_text(text, encoding)?;
+        Ok(self.update_incremental_md5(session_id, &bytes))
+    }
+
+    /// Feed chunk number `chunk_index` (counting from 0) of an upload that may arrive out of
+    /// order. Chunks are applied through `update_incremental_md5` strictly in index order:
+    /// a chunk that arrives early is buffered until every chunk before it has been applied.
+    /// While any chunk is buffered, `finalize_incremental_md5` refuses (returns an empty
+    /// string and keeps the session) and the strict variant throws `{ code: "chunk_gap" }`.
+    /// Returns false for an unknown session or an index that was already received.
+    #[wasm_bindgen]
+    pub fn update_incremental_md5_at(&self, session_id: &str, chunk_index: u32, data: &[u8]) -> bool {
+        let chunk_index = u64::from(chunk_index);
+        let ready = HASH_STATES.with(|states| {
+            let mut map = states.borrow_mut();
+            let session = map.get_mut(session_id)?;
+            if chunk_index < session.next_chunk || session.pending_chunks.contains_key(&chunk_index) {
+                return None;
+            }
+            session.pending_chunks.insert(chunk_index, data.to_vec());
+            let mut ready = Vec::new();
+            while let Some(chunk) = session.pending_chunks.remove(&session.next_chunk) {
+                ready.push(chunk);
+                session.next_chunk += 1;
+            }
+            Some(ready)
+        });
+
+        match ready {
+            Some(ready) => {
+                for chunk in &ready {
+                    self.update_incremental_md5(session_id, chunk);
+                }
+                true
+            }
+            None => {
+                console_log!(self.enable_log, "WARNING: Rejected chunk {} for incremental MD5 session: {}", chunk_index, session_id);
+                false
+            }
+        }
+    }
+
+    /// Have `update_incremental_md5` call `callback(bytes_processed, hex)` each time the bytes
+    /// it has fed into the session cross a multiple of `every_bytes` (once per update, even
+    /// if several multiples are crossed), with the full hex digest of everything hashed so
+    /// far. The session itself is not finalized. Callback exceptions are ignored. Returns
+    /// false for an unknown session or a zero interval.
+    #[wasm_bindgen]
+    pub fn set_interval_digest_callback(&self, session_id: &str, every_bytes: u64, callback: js_sys::Function) -> bool {
+        if every_bytes == 0 {
+            return false;
+        }
+        HASH_STATES.with(|states| match states.borrow_mut().get_mut(session_id) {
+            Some(session) => {
+                session.interval_digest = Some(ByteInterval::new(every_bytes, session.update_bytes, callback));
+                true
+            }
+            None => false,
+        })
+    }
+
+    /// Finalize the session and return the hex digest.
+    /// Returns an empty string and logs a warning if the session does not exist.
+    /// Deliberately synchronous (returns a string, not a Promise) so it can be called from
+    /// synchronous event handlers; keep it that way.
+    #[wasm_bindgen]
+    pub fn finalize_incremental_md5(&self, session_id: &str, md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        Ok(match self.finalize_session(session_id) {
+            Some(hash) => {
+                let hash_string = format!("{:x}", hash);
+                let truncated_hash = self.truncate(&hash_string, md5_length);
+                console_log!(self.enable_log, "Finalized incremental MD5 session: {}, result: {}", session_id, truncated_hash);
+                self.notify_finalized(session_id, &truncated_hash);
+                truncated_hash
+            }
+            None => String::new(),
+        })
+    }
+
+    /// `finalize_incremental_md5` returning the 16 raw digest bytes (a `Uint8Array`) instead
+    /// of hex; empty for an unknown session. `on_finalize` receives the full hex.
+    #[wasm_bindgen]
+    pub fn finalize_incremental_md5_bytes(&self, session_id: &str) -> Vec<u8> {
+        match self.finalize_session(session_id) {
+            Some(hash) => {
+                let hex = format!("{:x}", hash);
+                console_log!(self.enable_log, "Finalized incremental MD5 session: {}, result: {}", session_id, hex);
+                self.notify_finalized(session_id, &hex);
+                hash.to_vec()
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Like `finalize_incremental_md5`, but a missing session throws instead of returning an
+    /// ambiguous empty string: `{ code: "already_finalizing", session_id }` when the id was
+    /// finalized already (a second caller lost a finalize race), `{ code: "chunk_gap",
+    /// session_id }` while `update_incremental_md5_at` chunks are missing, and
+    /// `{ code: "not_found", session_id }` otherwise. Only the last 256 finalized ids are
+    /// remembered.
+    #[wasm_bindgen]
+    pub fn finalize_incremental_md5_strict(&self, session_id: &str, md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        let has_gap = HASH_STATES.with(|states| states.borrow().get(session_id).map(|session| !session.pending_chunks.is_empty()));
+        let code = match has_gap {
+            Some(false) => return self.finalize_incremental_md5(session_id, md5_length),
+            Some(true) => "chunk_gap",
+            None if FINALIZED_IDS.with(|ids| ids.borrow().iter().any(|id| id == session_id)) => "already_finalizing",
+            None => "not_found",
+        };
+        Err(js_object(&[("code", JsValue::from_str(code)), ("session_id", JsValue::from_str(session_id))]))
+    }
+
+    /// Finalize a session and restart it with a fresh hasher in the same step, returning the
+    /// finished segment's digest (`undefined` for an unknown session). The session keeps its
+    /// id and metadata; byte counts and checkpoint/interval callbacks start over. The
+    /// `set_on_finalize` callback fires as for `finalize_incremental_md5`. Like that method it
+    /// refuses while `update_incremental_md5_at` chunks are missing, returning `undefined` and
+    /// leaving the session untouched.
+    #[wasm_bindgen]
+    pub fn finalize_and_restart_incremental(&self, session_id: &str, md5_length: usize) -> Result<Option<String>, JsValue> {
+        check_md5_length(md5_length)?;
+        let restarted = HASH_STATES.with(|states| {
+            let mut map = states.borrow_mut();
+            let session = map.get_mut(session_id)?;
+            if !session.pending_chunks.is_empty() {
+                console_log!(self.enable_log, "WARNING: Incremental MD5 session has missing chunks, not restarting: {}", session_id);
+                return None;
+            }
+            let mut fresh = Session::new();
+            fresh.owner = session.owner;
+            fresh.meta = session.meta.clone();
+            let finished = std::mem::replace(session, fresh);
+            Some(self.truncate(&format!("{:x}", finished.hasher.finalize()), md5_length))
+        });
+        let hex = match restarted {
+            Some(hex) => hex,
+            None => return Ok(None),
+        };
+
+        console_log!(self.enable_log, "Finalized and restarted incremental MD5 session: {}, result: {}", session_id, hex);
+        self.notify_finalized(session_id, &hex);
+        Ok(Some(hex))
+    }
+
+    /// Register `callback(session_id, hex)` to run after every successful
+    /// `finalize_incremental_md5` on this instance, replacing any previous one. Exceptions it
+    /// throws are ignored and do not change the returned digest.
+    #[wasm_bindgen]
+    pub fn set_on_finalize(&self, callback: js_sys::Function) {
+        *self.on_finalize.borrow_mut() = Some(callback);
+    }
+
+    /// Digest of everything fed into the session so far, from a cloned hasher, so the
+    /// session keeps accepting updates. Returns an empty string for an unknown session.
+    #[wasm_bindgen]
+    pub fn peek_incremental_md5(&self, session_id: &str, md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        let peeked = HASH_STATES.with(|states| {
+            let map = states.borrow();
+            map.get(session_id).map(|session| format!("{:x}", session.hasher.clone().finalize()))
+        });
+        Ok(match peeked {
+            Some(hex) => self.truncate(&hex, md5_length),
+            None => {
+                console_log!(self.enable_log, "WARNING: Incremental MD5 session not found for peek: {}", session_id);
+                String::new()
+            }
+        })
+    }
+
+    /// `update_incremental_md5` followed by `peek_incremental_md5` in one call, for live digest
+    /// displays: returns the running digest after `data`, or an empty string if the update
+    /// was rejected (unknown session, strict empty update) or completed a capped session.
+    #[wasm_bindgen]
+    pub fn update_and_peek_incremental_md5(&self, session_id: &str, data: &[u8], md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        if self.update_incremental_md5(session_id, data) {
+            self.peek_incremental_md5(session_id, md5_length)
+        } else {
+            Ok(String::new())
+        }
+    }
+
+    /// Non-destructive snapshot of every live session, sorted by id: `[{ id, hex, bytes }]`,
+    /// where `hex` is the digest of everything hashed so far (from a cloned hasher, truncated
+    /// to `md5_length`) and `bytes` the total passed to `update_incremental_md5`.
+    #[wasm_bindgen]
+    pub fn peek_all(&self, md5_length: usize) -> Result<JsValue, JsValue> {
+        check_md5_length(md5_length)?;
+        let mut peeked: Vec<(String, String, u64)> = HASH_STATES.with(|states| {
+            states
+                .borrow()
+                .iter()
+                .map(|(id, session)| {
+                    let hex = self.truncate(&format!("{:x}", session.hasher.clone().finalize()), md5_length);
+                    (id.clone(), hex, session.update_bytes)
+                })
+                .collect()
+        });
+        peeked.sort();
+
+        Ok(peeked
+            .into_iter()
+            .map(|(id, hex, bytes)| {
+                js_object(&[
+                    ("id", JsValue::from_str(&id)),
+                    ("hex", JsValue::from_str(&hex)),
+                    ("bytes", JsValue::from(bytes as f64)),
+                ])
+            })
+            .collect::<js_sys::Array>()
+            .into())
+    }
+
+    /// Mark a save point: the digest of everything fed into the session so far, recorded
+    /// together with the byte count as the session's last commit. Unlike finalizing, the
+    /// session stays open for further updates. Returns an empty string for an unknown session.
+    #[wasm_bindgen]
+    pub fn commit_incremental_md5(&self, session_id: &str, md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        let committed = HASH_STATES.with(|states| {
+            let mut map = states.borrow_mut();
+            let session = map.get_mut(session_id)?;
+            let hex = format!("{:x}", session.hasher.clone().finalize());
+            session.last_commit = Some((hex.clone(), session.update_bytes));
+            Some((hex, session.update_bytes))
+        });
+
+        Ok(match committed {
+            Some((hex, bytes)) => {
+                let hex = self.truncate(&hex, md5_length);
+                console_log!(self.enable_log, "Committed incremental MD5 session: {}, bytes: {}, result: {}", session_id, bytes, hex);
+                hex
+            }
+            None => {
+                console_log!(self.enable_log, "WARNING: Incremental MD5 session not found for commit: {}", session_id);
+                String::new()
+            }
+        })
+    }
+
+    /// The session's last commit as `{ hex, bytes }` (`hex` truncated to `md5_length`),
+    /// independent of updates since; `undefined` for an unknown session or before any commit.
+    #[wasm_bindgen]
+    pub fn last_commit_incremental_md5(&self, session_id: &str, md5_length: usize) -> Result<JsValue, JsValue> {
+        check_md5_length(md5_length)?;
+        let last_commit = HASH_STATES.with(|states| states.borrow().get(session_id).and_then(|session| session.last_commit.clone()));
+        Ok(match last_commit {
+            Some((hex, bytes)) => js_object(&[
+                ("hex", JsValue::from_str(&self.truncate(&hex, md5_length))),
+                ("bytes", JsValue::from(bytes as f64)),
+            ]),
+            None => JsValue::UNDEFINED,
+        })
+    }
+
+    /// MD5 over the raw digests of several live sessions, concatenated in the order given, as
+    /// `combine_digests` would compute from their finalized digests. Each session's hasher is
+    /// cloned, so the sessions stay alive and unchanged. `undefined` if any id is missing.
+    #[wasm_bindgen]
+    pub fn combine_sessions(&self, session_ids: Vec<String>, md5_length: usize) -> Result<Option<String>, JsValue> {
+        check_md5_length(md5_length)?;
+        let mut hasher = Md5::new();
+        let found = HASH_STATES.with(|states| {
+            let map = states.borrow();
+            for id in &session_ids {
+                hasher.update(map.get(id)?.hasher.clone().finalize());
+            }
+            Some(())
+        });
+        Ok(found.map(|()| self.truncate(&format!("{:x}", hasher.finalize()), md5_length)))
+    }
+
+    /// Effective hashing throughput of a session's updates so far, in MB/s (MB = 2^20 bytes):
+    /// total bytes passed to `update_incremental_md5` over the time spent inside those calls.
+    /// Returns 0 before any measurable time has elapsed and `undefined` for an unknown session.
+    #[wasm_bindgen]
+    pub fn incremental_throughput_mbps(&self, session_id: &str) -> Option<f64> {
+        HASH_STATES.with(|states| {
+            states.borrow().get(session_id).map(|session| {
+                if session.update_ms > 0.0 {
+                    (session.update_bytes as f64 / (1024.0 * 1024.0)) / (session.update_ms / 1000.0)
+                } else {
+                    0.0
+                }
+            })
+        })
+    }
+
+    /// Lengths of the updates a session received, in order, while `set_track_update_sizes`
+    /// was on (empty if it never was); `undefined` for an unknown session.
+    #[wasm_bindgen]
+    pub fn session_update_sizes(&self, session_id: &str) -> Option<Vec<usize>> {
+        HASH_STATES.with(|states| states.borrow().get(session_id).map(|session| session.update_sizes.clone()))
+    }
+
+    /// Read `stream` to completion, feeding every chunk into the named session without
+    /// finalizing it, so several sources can be concatenated into one running digest. Each
+    /// chunk goes through `update_incremental_md5`, so byte caps, callbacks and
+    /// `set_track_update_sizes` apply as for direct updates (empty chunks are skipped); it
+    /// yields every 2 MB read, like `calculate_md5_stream_async`.
+    /// Rejects if the session is missing (also if it disappears mid-stream, e.g. on reaching
+    /// its cap) or the stream errors, and with `{ code: "chunk_gap", session_id }` while
+    /// `update_incremental_md5_at` chunks are missing; bytes read before a failure remain in
+    /// the session.
+    #[wasm_bindgen]
+    pub async fn pipe_stream_to_session_async(&self, session_id: &str, stream: web_sys::ReadableStream) -> Result<bool, JsValue> {
+        let check = || match HASH_STATES.with(|states| states.borrow().get(session_id).map(|session| session.pending_chunks.is_empty())) {
+            Some(true) => Ok(()),
+            Some(false) => Err(js_object(&[("code", JsValue::from_str("chunk_gap")), ("session_id", JsValue::from_str(session_id))])),
+            None => Err(js_error(&format!("Incremental MD5 session not found: {}", session_id))),
+        };
+        check()?;
+
+        let total = self.read_stream(&stream, |chunk| {
+            check()?;
+            if !chunk.is_empty() {
+                self.update_incremental_md5(session_id, chunk);
+            }
+            Ok(())
+        })
+        .await?;
+
+        console_log!(self.enable_log, "Piped stream into incremental MD5 session: {}, bytes: {}", session_id, total);
+        Ok(true)
+    }
+
+    /// Wrap `source` in a pass-through stream that hashes each chunk as the consumer pulls it.
+    /// Returns `{ stream, digest_promise }`: `stream` yields the source's bytes unchanged and
+    /// `digest_promise` resolves with the full hex MD5 once the source closes. A source error
+    /// (or a non-`Uint8Array` chunk) errors `stream` and rejects `digest_promise`; cancelling
+    /// `stream` cancels the source and rejects `digest_promise` with the cancel reason.
+    #[wasm_bindgen]
+    pub fn md5_tee(&self, source: web_sys::ReadableStream) -> Result<JsValue, JsValue> {
+        let mut settle = None;
+        let digest_promise = js_sys::Promise::new(&mut |resolve, reject| settle = Some((resolve, reject)));
+        let (resolve, reject) = settle.ok_or_else(|| js_error("Promise executor did not run"))?;
+        let tee = Rc::new(Tee {
+            reader: source.get_reader().unchecked_into(),
+            hasher: RefCell::new(Some(Md5::new())),
+            bytes_hashed: Rc::clone(&self.bytes_hashed),
+            resolve,
+            reject,
+        });
+
+        let pull_tee = Rc::clone(&tee);
+        let pull = Closure::<dyn FnMut(web_sys::ReadableStreamDefaultController) -> js_sys::Promise>::new(
+            move |controller: web_sys::ReadableStreamDefaultController| {
+                let tee = Rc::clone(&pull_tee);
+                wasm_bindgen_futures::future_to_promise(async move {
+                    tee.pull(&controller).await;
+                    Ok(JsValue::UNDEFINED)
+                })
+            },
+        );
+        let cancel_tee = Rc::clone(&tee);
+        let cancel = Closure::<dyn FnMut(JsValue) -> js_sys::Promise>::new(move |reason: JsValue| {
+            cancel_tee.fail(&reason);
+            cancel_tee.reader.cancel_with_reason(&reason)
+        });
+
+        let stream = web_sys::ReadableStream::new_with_underlying_source(
+            js_object(&[("pull", pull.into_js_value()), ("cancel", cancel.into_js_value())]).unchecked_ref(),
+        )?;
+        console_log!(self.enable_log, "Started MD5 tee stream");
+        Ok(js_object(&[("stream", stream.into()), ("digest_promise", digest_promise.into())]))
+    }
+
+    /// Cancel and discard an active incremental session.
+    #[wasm_bindgen]
+    pub fn cancel_incremental_md5(&self, session_id: &str) -> bool {
+        HASH_STATES.with(|states| {
+            let removed = states.borrow_mut().remove(session_id).is_some();
+            if removed {
+                console_log!(self.enable_log, "Cancelled incremental MD5 session: {}", session_id);
+            } else {
+                console_log!(self.enable_log, "Incremental MD5 session not found for cancellation: {}", session_id);
+            }
+            removed
+        })
+    }
+
+    /// Begin a rolling session whose digest always covers the last `window_bytes` bytes pushed.
+    /// Returns false (and starts nothing) for a zero-sized window; throws
+    /// `{ code: "session_limit" }` at the `set_max_sessions` limit.
+    #[wasm_bindgen]
+    pub fn start_rolling_md5(&self, session_id: &str, window_bytes: usize) -> Result<bool, JsValue> {
+        if window_bytes == 0 {
+            return Ok(false);
+        }
+        self.check_session_limit(&ROLLING_STATES, session_id)?;
+        ROLLING_STATES.with(|states| {
+            states.borrow_mut().insert(
+                session_id.to_string(),
+                RollingWindow {
+                    owner: self.owner,
+                    capacity: window_bytes,
+                    bytes: VecDeque::with_capacity(window_bytes),
+                    hasher: Md5::new(),
+                },
+            );
+        });
+        console_log!(self.enable_log, "Started rolling MD5 session: {}, window: {}", session_id, window_bytes);
+        Ok(true)
+    }
+
+    /// Append bytes to a rolling session. While the window has room this is a plain
+    /// incremental update; once bytes must be evicted the whole retained window is
+    /// re-hashed, so each overflowing push costs O(window_bytes).
+    /// Returns false for an unknown session.
+    #[wasm_bindgen]
+    pub fn push_rolling_md5(&self, session_id: &str, data: &[u8]) -> bool {
+        ROLLING_STATES.with(|states| match states.borrow_mut().get_mut(session_id) {
+            Some(window) => {
+                window.push(data);
+                true
+            }
+            None => {
+                console_log!(self.enable_log, "Rolling MD5 session not found: {}", session_id);
+                false
+            }
+        })
+    }
+
+    /// Digest of the bytes currently inside the window. The session stays live.
+    /// Returns an empty string for an unknown session.
+    #[wasm_bindgen]
+    pub fn digest_rolling_md5(&self, session_id: &str, md5_length: usize) -> Result<String, JsValue> {
+        check_md5_length(md5_length)?;
+        Ok(ROLLING_STATES.with(|states| {
+            states.borrow().get(session_id).map_or_else(String::new, |window| {
+                self.truncate(&format!("{:x}", window.hasher.clone().finalize()), md5_length)
+            })
+        }))
+    }
+
+    /// Discard a rolling session.
+    #[wasm_bindgen]
+    pub fn cancel_rolling_md5(&self, session_id: &str) -> bool {
+        ROLLING_STATES.with(|states| states.borrow_mut().remove(session_id).is_some())
+    }
+
+    /// Log a warning when `items` digests truncated to `md5_length` have at least a 1% chance
+    /// of containing a collision.
+    fn warn_if_collision_prone(&self, md5_length: usize, items: u64) {
+        if !self.enable_log {
+            return;
+        }
+        let probability = self.truncation_collision_estimate(md5_length, items);
+        if probability >= 0.01 {
+            console_log!(
+                true,
+                "WARNING: {}-char digests over {} items collide with ~{:.1}% probability; use a longer md5_length",
+                self.rounded_length(md5_length).min(32),
+                items,
+                probability * 100.0
+            );
+        }
+    }
+
+    /// `md5_length` after applying this instance's `LengthRounding` policy.
+    fn rounded_length(&self, md5_length: usize) -> usize {
+        match self.length_rounding {
+            LengthRounding::Exact => md5_length,
+            LengthRounding::RoundDownToByte if (17..32).contains(&md5_length) => 16,
+            LengthRounding::RoundUpToByte if (17..32).contains(&md5_length) => 32,
+            LengthRounding::RoundDownToByte => md5_length - md5_length % 2,
+            LengthRounding::RoundUpToByte => md5_length + md5_length % 2,
+        }
+    }
+
+    /// Truncate a hex digest to `md5_length` chars, honouring the rounding policy.
+    fn truncate(&self, hash_string: &str, md5_length: usize) -> String {
+        Self::truncate_hash(hash_string, self.rounded_length(md5_length))
+    }
+
+    /// Leading `md5_length` chars of `hash_string`, or all of it when shorter. Never slices
+    /// past the end, so a short input cannot panic (which would abort the wasm instance).
+    fn truncate_hash(hash_string: &str, md5_length: usize) -> String {
+        match hash_string.get(..md5_length) {
+            Some(prefix) => prefix.to_string(),
+            None => hash_string.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "session-state")]
+#[wasm_bindgen]
+impl Md5Calculator {
+    /// Diagnostic: the raw MD5 chaining registers `(a, b, c, d)` of a live session, as of
+    /// the last complete 64-byte block (buffered tail bytes are not yet mixed in).
+    /// Reads a mirrored state, so the ongoing session is not disturbed.
+    /// Returns `undefined` for an unknown session.
+    #[wasm_bindgen]
+    pub fn incremental_state_words(&self, session_id: &str) -> Option<Vec<u32>> {
+        HASH_STATES.with(|states| {
+            states
+                .borrow()
+                .get(session_id)
+                .map(|session| session.hasher.words().to_vec())
+        })
+    }
+
+    /// Serialize a live session's hasher state (see `state::Md5State::export` for the
+    /// layout) so it can be persisted and resumed later with `import_incremental_md5`.
+    /// Returns `undefined` for an unknown session; the session itself is untouched.
+    #[wasm_bindgen]
+    pub fn export_incremental_md5(&self, session_id: &str) -> Option<Vec<u8>> {
+        HASH_STATES.with(|states| states.borrow().get(session_id).map(|session| session.hasher.export()))
+    }
+
+    /// Recreate a session from exported state, replacing any session with the same id.
+    /// Returns false (and changes nothing) if `state` is not a valid export; throws
+    /// `{ code: "session_limit" }` at the `set_max_sessions` limit.
+    #[wasm_bindgen]
+    pub fn import_incremental_md5(&self, session_id: &str, state: &[u8]) -> Result<bool, JsValue> {
+        self.check_session_limit(&HASH_STATES, session_id)?;
+        Ok(match state::Md5State::import(state) {
+            Some(hasher) => {
+                let mut session = Session::new();
+                session.update_bytes = hasher.total_len();
+                session.hasher = hasher;
+                self.insert_session(session_id, session);
+                console_log!(self.enable_log, "Imported incremental MD5 session: {}", session_id);
+                true
+            }
+            None => {
+                console_log!(self.enable_log, "WARNING: Invalid exported MD5 state for session: {}", session_id);
+                false
+            }
+        })
+    }
+
+    /// Have `update_incremental_md5` call `callback(state: Uint8Array, bytesProcessed)` each
+    /// time the session's byte total crosses a multiple of `every_bytes` (once per update,
+    /// even if several multiples are crossed). `state` is an `export_incremental_md5`
+    /// snapshot. Callback exceptions are ignored. Returns false for an unknown session or a
+    /// zero interval.
+    #[wasm_bindgen]
+    pub fn set_checkpoint_callback(&self, session_id: &str, every_bytes: u64, callback: js_sys::Function) -> bool {
+        if every_bytes == 0 {
+            return false;
+        }
+        HASH_STATES.with(|states| match states.borrow_mut().get_mut(session_id) {
+            Some(session) => {
+                session.checkpoint = Some(ByteInterval::new(every_bytes, session.update_bytes, callback));
+                true
+            }
+            None => false,
+        })
+    }
+
+    /// Cancel every incremental MD5 session, from any instance, and return what was in flight,
+    /// sorted by id, as `[{ id, state, bytes, hex }]`: `state` is the `export_incremental_md5`
+    /// snapshot (ready for `import_incremental_md5` after a reload), `bytes` the bytes hashed
+    /// so far and `hex` the digest of those bytes truncated to `md5_length`. Rolling and
+    /// SHA-256 sessions have no exportable state and are left running, as are sessions still
+    /// missing `update_incremental_md5_at` chunks, whose buffered chunks the state would lose.
+    /// Only available with the `session-state` feature.
+    #[wasm_bindgen]
+    pub fn drain_sessions(&self, md5_length: usize) -> Result<JsValue, JsValue> {
+        check_md5_length(md5_length)?;
+        let mut drained: Vec<(String, Session)> = HASH_STATES.with(|states| {
+            let mut map = states.borrow_mut();
+            let ids: Vec<String> = map.iter().filter(|(_, session)| session.pending_chunks.is_empty()).map(|(id, _)| id.clone()).collect();
+            ids.into_iter().filter_map(|id| map.remove_entry(&id)).collect()
+        });
+        drained.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let snapshot = js_sys::Array::new();
+        for (id, session) in &drained {
+            let hex = self.truncate(&format!("{:x}", session.hasher.clone().finalize()), md5_length);
+            snapshot.push(&js_object(&[
+                ("id", JsValue::from_str(id)),
+                ("state", js_sys::Uint8Array::from(session.hasher.export().as_slice()).into()),
+                ("bytes", JsValue::from(session.update_bytes as f64)),
+                ("hex", JsValue::from_str(&hex)),
+            ]));
+        }
+        console_log!(self.enable_log, "Drained {} incremental MD5 sessions", drained.len());
+        Ok(snapshot.into())
+    }
+}
+
+#[cfg(feature = "sha256")]
+#[wasm_bindgen]
+impl Md5Calculator {
+    /// SHA-256 of `data` as 64 lowercase hex chars, hashed through the same chunked,
+    /// yielding loop as `calculate_md5_async`.
+    #[wasm_bindgen]
+    pub async fn calculate_sha256_async(&self, data: &[u8]) -> String {
+        let mut hasher = sha2::Sha256::new();
+        self.feed_chunked(data, |chunk| hasher.update(chunk)).await;
+        let hex = format!("{:x}", hasher.finalize());
+        console_log!(self.enable_log, "Async SHA-256 calculation completed: {}", hex);
+        hex
+    }
+
+    /// Begin an incremental SHA-256 session. SHA-256 sessions have their own id space,
+    /// separate from MD5 sessions. Returns true if this replaced a live session with the
+    /// same id; throws `{ code: "session_limit" }` at the `set_max_sessions` limit.
+    #[wasm_bindgen]
+    pub fn start_incremental_sha256(&self, session_id: &str) -> Result<bool, JsValue> {
+        self.check_session_limit(&SHA256_STATES, session_id)?;
+        let clobbered = SHA256_STATES.with(|states| states.borrow_mut().insert(session_id.to_string(), (self.owner, sha2::Sha256::new())).is_some());
+        console_log!(self.enable_log, "Started incremental SHA-256 session: {}", session_id);
+        Ok(clobbered)
+    }
+
+    /// Feed data into an incremental SHA-256 session; false for an unknown session.
+    #[wasm_bindgen]
+    pub fn update_incremental_sha256(&self, session_id: &str, data: &[u8]) -> bool {
+        let updated = SHA256_STATES.with(|states| match states.borrow_mut().get_mut(session_id) {
+            Some((_, hasher)) => {
+                hasher.update(data);
+                true
+            }
+            None => false,
+        });
+        if updated {
+            self.count_hashed(data.len() as u64);
+        } else {
+            console_log!(self.enable_log, "Incremental SHA-256 session not found: {}", session_id);
+        }
+        updated
+    }
+
+    /// Finish an incremental SHA-256 session and return its 64-char hex digest, or an empty
+    /// string for an unknown session.
+    #[wasm_bindgen]
+    pub fn finalize_incremental_sha256(&self, session_id: &str) -> String {
+        match SHA256_STATES.with(|states| states.borrow_mut().remove(session_id)) {
+            Some((_, hasher)) => format!("{:x}", hasher.finalize()),
+            None => {
+                console_log!(self.enable_log, "WARNING: Incremental SHA-256 session not found for finalization: {}", session_id);
+                String::new()
+            }
+        }
+    }
+
+    /// Cancel and discard an incremental SHA-256 session.
+    #[wasm_bindgen]
+    pub fn cancel_incremental_sha256(&self, session_id: &str) -> bool {
+        SHA256_STATES.with(|states| states.borrow_mut().remove(session_id).is_some())
+    }
+}
+
+#[cfg(feature = "session-state")]
+impl Session {
+    /// If the last update crossed a checkpoint boundary, advance it and return the callback
+    /// with its arguments. Invoked by the caller after releasing the sessions borrow, so the
+    /// callback may safely call back into the calculator.
+    fn due_checkpoint(&mut self) -> Option<DeferredCall> {
+        let processed = self.update_bytes;
+        let checkpoint = self.checkpoint.as_mut()?;
+        if !checkpoint.advance(processed) {
+            return None;
+        }
+        let state = js_sys::Uint8Array::from(self.hasher.export().as_slice());
+        Some((checkpoint.callback.clone(), js_sys::Array::of2(&state, &JsValue::from(processed as f64))))
+    }
+}
+
+impl Md5Calculator {
+    /// Store `session` under `session_id` as owned by this instance, returning the session it
+    /// replaced, if any.
+    fn insert_session(&self, session_id: &str, mut session: Session) -> Option<Session> {
+        session.owner = self.owner;
+        FINALIZED_IDS.with(|ids| ids.borrow_mut().retain(|id| id != session_id));
+        HASH_STATES.with(|states| states.borrow_mut().insert(session_id.to_string(), session))
+    }
+
+    /// Fail with `{ code: "session_limit", session_id }` if starting `session_id` in `states`
+    /// would exceed `max_sessions`.
+    fn check_session_limit<V>(&self, states: &'static LocalKey<RefCell<HashMap<String, V>>>, session_id: &str) -> Result<(), JsValue> {
+        let reached = states.with(|states| {
+            let map = states.borrow();
+            !map.contains_key(session_id) && map.len() >= self.max_sessions
+        });
+        if !reached {
+            return Ok(());
+        }
+        console_log!(self.enable_log, "WARNING: Session limit of {} reached, not starting: {}", self.max_sessions, session_id);
+        Err(js_object(&[("code", JsValue::from_str("session_limit")), ("session_id", JsValue::from_str(session_id))]))
+    }
+
+    /// Take the session out of the map and finish its hash, remembering the id for
+    /// `finalize_incremental_md5_strict`. `None` (after logging why) for an unknown session or
+    /// one still missing `update_incremental_md5_at` chunks, which is left in place.
+    fn finalize_session(&self, session_id: &str) -> Option<md5::digest::Output<Md5>> {
+        let session = utils::with_active_session(session_id, || HASH_STATES.with(|states| {
+            let mut map = states.borrow_mut();
+            if map.get(session_id).is_some_and(|session| !session.pending_chunks.is_empty()) {
+                console_log!(self.enable_log, "WARNING: Incremental MD5 session has missing chunks, not finalizing: {}", session_id);
+                None
+            } else {
+                let session = map.remove(session_id);
+                if session.is_none() {
+                    console_log!(self.enable_log, "WARNING: Incremental MD5 session not found for finalization: {}", session_id);
+                }
+                session
+            }
+        }))?;
+        remember_finalized(session_id);
+        Some(session.hasher.finalize())
+    }
+
+    /// Run the `set_on_finalize` callback, if any, for a finalized session.
+    fn notify_finalized(&self, session_id: &str, hex: &str) {
+        let callback = self.on_finalize.borrow().clone();
+        if let Some(callback) = callback {
+            let args = js_sys::Array::of2(&JsValue::from_str(session_id), &JsValue::from_str(hex));
+            fire_callbacks(vec![(callback, args)]);
+        }
+    }
+
+    /// Add `len` bytes to the lifetime `total_bytes_hashed` counter.
+    fn count_hashed(&self, len: u64) {
+        self.bytes_hashed.set(self.bytes_hashed.get().saturating_add(len));
+    }
+
+    /// Short per-instance correlation id for log lines, e.g. `000a`.
+    fn next_call_id(&self) -> String {
+        let id = self.call_counter.get().wrapping_add(1);
+        self.call_counter.set(id);
+        format!("{:04x}", id)
+    }
+
+    /// Full MD5 digest of `data`, hashed through the cooperative chunk loop.
+    async fn digest_async(&self, data: &[u8]) -> md5::digest::Output<Md5> {
+        let mut hasher = Md5::new();
+        self.feed_chunked(data, |chunk| hasher.update(chunk)).await;
+        hasher.finalize()
+    }
+
+    /// Drain `stream` to completion, passing each `Uint8Array` chunk to `sink` in order and
+    /// taking a `yield_point` every `yield_interval` bytes read. Resolves with the total byte
+    /// count; stream errors, non-`Uint8Array` chunks and sink errors reject (the reader is
+    /// cancelled in the latter two cases).
+    async fn read_stream(
+        &self,
+        stream: &web_sys::ReadableStream,
+        mut sink: impl FnMut(&[u8]) -> Result<(), JsValue>,
+    ) -> Result<u64, JsValue> {
+        let reader: web_sys::ReadableStreamDefaultReader = stream.get_reader().unchecked_into();
+        let mut total = 0u64;
+        let mut bytes_since_yield = 0usize;
+
+        loop {
+            let result = wasm_bindgen_futures::JsFuture::from(reader.read()).await?;
+            if js_sys::Reflect::get(&result, &JsValue::from_str("done"))?.is_truthy() {
+                break;
+            }
+            let outcome = js_sys::Reflect::get(&result, &JsValue::from_str("value"))?
+                .dyn_into::<js_sys::Uint8Array>()
+                .map_err(|_| js_error("ReadableStream yielded a non-Uint8Array chunk"))
+                .and_then(|chunk| {
+                    let bytes = chunk.to_vec();
+                    total += bytes.len() as u64;
+                    bytes_since_yield += bytes.len();
+                    sink(&bytes)
+                });
+            if let Err(error) = outcome {
+                let _ = reader.cancel();
+                return Err(error);
+            }
+            if bytes_since_yield >= self.yield_interval {
+                bytes_since_yield = 0;
+                self.yield_point().await;
+            }
+        }
+
+        reader.release_lock();
+        Ok(total)
+    }
+
+    /// Cooperative yield point shared by every async loop: yields, then waits here for as long
+    /// as the calculator is paused. A no-op while yielding is disabled, so no loop observes
+    /// `pause` then.
+    async fn yield_point(&self) {
+        if !self.yielding_enabled {
+            return;
+        }
+        match self.scheduler_priority.as_deref().and_then(post_task) {
+            Some(task) => {
+                let _ = wasm_bindgen_futures::JsFuture::from(task).await;
+            }
+            None => yield_now().await,
+        }
+        while self.paused.get() {
+            let resumed = js_sys::Promise::new(&mut |resolve, _| self.resume_waiters.borrow_mut().push(resolve));
+            let _ = wasm_bindgen_futures::JsFuture::from(resumed).await;
+        }
+    }
+
+    /// Remove every incremental, rolling and SHA-256 session this instance owns (started last)
+    /// from the global maps, returning how many were dropped.
+    fn drop_owned_sessions(&self) -> usize {
+        let owner = self.owner;
+        let mut dropped = HASH_STATES.with(|states| {
+            let mut map = states.borrow_mut();
+            let before = map.len();
+            map.retain(|_, session| session.owner != owner);
+            before - map.len()
+        });
+        dropped += ROLLING_STATES.with(|states| {
+            let mut map = states.borrow_mut();
+            let before = map.len();
+            map.retain(|_, window| window.owner != owner);
+            before - map.len()
+        });
+        #[cfg(feature = "sha256")]
+        {
+            dropped += SHA256_STATES.with(|states| {
+                let mut map = states.borrow_mut();
+                let before = map.len();
+                map.retain(|_, (session_owner, _)| *session_owner != owner);
+                before - map.len()
+            });
+        }
+        dropped
+    }
+
+    /// Chunk size `feed_chunked` uses for an input of `data_len` bytes, or `None` when the
+    /// input is hashed in a single call (small inputs, or yielding disabled).
+    fn chunk_size_for(&self, data_len: usize) -> Option<usize> {
+        if !self.yielding_enabled || data_len <= 512 * 1024 {
+            None
+        } else if self.chunk_size.is_some() {
+            self.chunk_size
+        } else if data_len > 10 * 1024 * 1024 {
+            Some(256 * 1024)
+        } else {
+            Some(128 * 1024)
+        }
+    }
+
+    /// Number of byte-triggered yields `feed_chunked` performs for `data_len` bytes.
+    /// Time-budget yields (`set_max_block_ms`) are not predictable and not included.
+    fn expected_yields(&self, data_len: usize) -> usize {
+        let chunk_size = match self.chunk_size_for(data_len) {
+            Some(chunk_size) => chunk_size,
+            None => return 0,
+        };
+        // A yield fires once a run of whole chunks reaches the yield interval.
+        let group = self.yield_interval.div_ceil(chunk_size) * chunk_size;
+        let remainder = data_len % group;
+        data_len / group + usize::from(remainder >= self.yield_interval)
+    }
+
+    /// Pass `data` to `sink` in order, chunking and yielding every `yield_interval` bytes for large inputs
+    /// to keep the UI responsive. Small inputs are passed through in a single call.
+    async fn feed_chunked(&self, data: &[u8], sink: impl FnMut(&[u8])) {
+        self.feed_chunked_with(data, sink, None, None).await;
+    }
+
+    /// `feed_chunked`, also reporting `(bytes_processed, data.len())` to `on_progress` at each
+    /// yield taken before the end of `data` (completion is left to the caller to report), and
+    /// stopping at the first yield point after `signal` aborts. Returns false if it stopped.
+    async fn feed_chunked_with(
+        &self,
+        data: &[u8],
+        mut sink: impl FnMut(&[u8]),
+        on_progress: Option<&js_sys::Function>,
+        signal: Option<&web_sys::AbortSignal>,
+    ) -> bool {
+        self.feed_ranges(data.len(), |range| sink(&data[range]), on_progress, signal).await
+    }
+
+    /// The loop behind `feed_chunked_with`, over `len` bytes the sink reads itself: `sink`
+    /// receives consecutive ranges covering `0..len`, for sources that are not one slice in
+    /// WASM memory (e.g. a JS `Uint8Array` copied in piece by piece). Each range is counted in
+    /// `total_bytes_hashed` once it has been fed.
+    async fn feed_ranges(
+        &self,
+        len: usize,
+        mut sink: impl FnMut(std::ops::Range<usize>),
+        on_progress: Option<&js_sys::Function>,
+        signal: Option<&web_sys::AbortSignal>,
+    ) -> bool {
+        if len == 0 {
+            // Sinks (and the callbacks behind them) still see one, empty, chunk.
+            sink(0..0);
+            return true;
+        }
+        let mut processed = 0usize;
+        let step = |max: usize| {
+            let end = len.min(processed.saturating_add(max));
+            let fed = end - processed;
+            if fed > 0 {
+                sink(processed..end);
+                self.count_hashed(fed as u64);
+                processed = end;
+            }
+            fed
+        };
+        self.feed_steps(len, step, on_progress, signal).await
+    }
+
+    /// The chunk loop every in-memory async method runs on, for inputs that are not a plain
+    /// run of bytes (decompressed, XOR-ed, sampled, ...): `step(max)` processes up to `max`
+    /// more bytes of input and returns how many it did, 0 once the input is exhausted.
+    /// `len` (`usize::MAX` when not known up front) picks the chunk size via `chunk_size_for`
+    /// and is the total reported to `on_progress`; an unchunked input is processed by one
+    /// unbounded step with no yield. Yields every `yield_interval` bytes or `max_block_ms`,
+    /// under the calculator's yielding and scheduler policy, and stops at the first yield
+    /// after `signal` aborts (returning false). Nothing is counted in `total_bytes_hashed`.
+    async fn feed_steps(
+        &self,
+        len: usize,
+        mut step: impl FnMut(usize) -> usize,
+        on_progress: Option<&js_sys::Function>,
+        signal: Option<&web_sys::AbortSignal>,
+    ) -> bool {
+        let chunk_size = match self.chunk_size_for(len) {
+            Some(chunk_size) => chunk_size,
+            None => {
+                while step(usize::MAX) > 0 {}
+                return true;
+            }
+        };
+
+        let time_budget = self.max_block_ms > 0.0;
+        let mut last_yield = if time_budget { now_ms() } else { 0.0 };
+        let mut bytes_since_yield = 0usize;
+        let mut processed = 0usize;
+
+        loop {
+            let done = step(chunk_size);
+            if done == 0 {
+                return true;
+            }
+            bytes_since_yield += done;
+            processed += done;
+
+            let over_budget = time_budget && now_ms() - last_yield >= self.max_block_ms;
+            if bytes_since_yield >= self.yield_interval || over_budget {
+                bytes_since_yield = 0;
+                if let Some(on_progress) = on_progress.filter(|_| processed < len) {
+                    report_progress(on_progress, processed, len);
+                }
+                self.yield_point().await;
+                if signal.is_some_and(|signal| signal.aborted()) {
+                    return false;
+                }
+                if time_budget {
+                    last_yield = now_ms();
+                }
+            }
+        }
+    }
+}
+
+/// Reject an `md5_length` that cannot select any part of a digest: it must be 1..=32 hex chars.
+/// Every public method taking an `md5_length` checks it before doing any work, so a bad length
+/// throws rather than consuming a session or hashing input for nothing.
+fn check_md5_length(md5_length: usize) -> Result<(), JsValue> {
+    if (1..=32).contains(&md5_length) {
+        Ok(())
+    } else {
+        Err(js_error(&format!("md5_length must be between 1 and 32, got {}", md5_length)))
+    }
+}
+
+/// Whether `undefined` appears in `value` itself, an array element or an own enumerable
+/// property, at any depth. `value` must be acyclic (e.g. already accepted by `JSON.stringify`).
+fn contains_undefined(value: &JsValue) -> bool {
+    if value.is_undefined() {
+        return true;
+    }
+    if let Some(array) = value.dyn_ref::<js_sys::Array>() {
+        return array.iter().any(|item| contains_undefined(&item));
+    }
+    value.is_object()
+        && js_sys::Object::values(value.unchecked_ref()).iter().any(|item| contains_undefined(&item))
+}
+
+/// Call a progress callback with `(bytes_processed, total_bytes)`, ignoring exceptions.
+fn report_progress(callback: &js_sys::Function, processed: usize, total: usize) {
+    let _ = callback.call2(&JsValue::NULL, &JsValue::from(processed as f64), &JsValue::from(total as f64));
+}
+
+/// Record `session_id` as finalized, forgetting the oldest id beyond `FINALIZED_ID_CAPACITY`.
+fn remember_finalized(session_id: &str) {
+    FINALIZED_IDS.with(|ids| {
+        let mut ids = ids.borrow_mut();
+        ids.retain(|id| id != session_id);
+        if ids.len() == FINALIZED_ID_CAPACITY {
+            ids.pop_front();
+        }
+        ids.push_back(session_id.to_string());
+    });
+}
+
+/// Let the event loop run by awaiting an already-resolved promise.
+async fn yield_now() {
+    let _ = wasm_bindgen_futures::JsFuture::from(js_sys::Promise::resolve(&JsValue::NULL)).await;
+}
+
+/// Schedule an empty task with `scheduler.postTask` at `priority`, or `None` where the
+/// Scheduler API is unavailable.
+fn post_task(priority: &str) -> Option<js_sys::Promise> {
+    let scheduler = js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("scheduler")).ok()?;
+    let post_task = js_sys::Reflect::get(&scheduler, &JsValue::from_str("postTask"))
+        .ok()?
+        .dyn_into::<js_sys::Function>()
+        .ok()?;
+    let options = js_object(&[("priority", JsValue::from_str(priority))]);
+    post_task
+        .call2(&scheduler, &Closure::once_into_js(|| {}), &options)
+        .ok()?
+        .dyn_into()
+        .ok()
+}
+
+/// Resolve after roughly `ms` milliseconds via the global `setTimeout` (window or worker);
+/// returns immediately where there is none.
+async fn sleep_ms(ms: i32) {
+    let set_timeout = js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("setTimeout"))
+        .ok()
+        .and_then(|f| f.dyn_into::<js_sys::Function>().ok());
+    if let Some(set_timeout) = set_timeout {
+        let timer = js_sys::Promise::new(&mut |resolve, _| {
+            let _ = set_timeout.call2(&JsValue::NULL, &resolve, &JsValue::from(ms));
+        });
+        let _ = wasm_bindgen_futures::JsFuture::from(timer).await;
+    }
+}
+
+/// High-resolution timestamp in milliseconds from the window or worker `performance`
+/// object, falling back to `Date.now()` where neither is available.
+fn now_ms() -> f64 {
+    let performance = match web_sys::window() {
+        Some(window) => window.performance(),
+        None => js_sys::global()
+            .dyn_into::<web_sys::WorkerGlobalScope>()
+            .ok()
+            .and_then(|scope| scope.performance()),
+    };
+    performance.map_or_else(js_sys::Date::now, |performance| performance.now())
+}
+
+/// Throughput in MiB per second; 0 when no time elapsed.
+fn mb_per_second(bytes: usize, elapsed_ms: f64) -> f64 {
+    if elapsed_ms > 0.0 {
+        (bytes as f64 / (1024.0 * 1024.0)) / (elapsed_ms / 1000.0)
+    } else {
+        0.0
+    }
+}
+
+/// Current size of WASM linear memory in bytes; 0 when not running on wasm32.
+fn linear_memory_bytes() -> usize {
+    #[cfg(target_arch = "wasm32")]
+    {
+        core::arch::wasm32::memory_size(0) * 65536
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        0
+    }
+}
+
+fn js_error(message: &str) -> JsValue {
+    js_sys::Error::new(message).into()
+}
+
+/// Build a plain JS object from `(key, value)` pairs.
+fn js_object(fields: &[(&str, JsValue)]) -> JsValue {
+    let object = js_sys::Object::new();
+    for (key, value) in fields {
+        // Setting a property on a fresh plain object cannot fail.
+        let _ = js_sys::Reflect::set(&object, &JsValue::from_str(key), value);
+    }
+    object.into()
+}
+
+/// Encode `text` as UTF-8 or latin1 (ISO-8859-1); `encoding` names are case-insensitive.
+fn encode_text(text: &str, encoding: &str) -> Result<Vec<u8>, JsValue> {
+    match encoding.to_ascii_lowercase().as_str() {
+        "utf-8" | "utf8" => Ok(text.as_bytes().to_vec()),
+        "latin1" | "latin-1" | "iso-8859-1" => text
+            .chars()
+            .map(|c| match c as u32 {
+                code @ 0..=0xff => Ok(code as u8),
+                _ => Err(js_error(&format!("Character {:?} is not representable in latin1", c))),
+            })
+            .collect(),
+        _ => Err(js_error(&format!("Unsupported text encoding: {}", encoding))),
+    }
+}
+
+/// Split a digest into two `u64` halves read in `endian` byte order.
+fn digest_u64x2(digest: &[u8], endian: Endian) -> [u64; 2] {
+    let mut halves = [0u64; 2];
+    for (half, bytes) in halves.iter_mut().zip(digest.chunks_exact(8)) {
+        let mut word = [0u8; 8];
+        word.copy_from_slice(bytes);
+        *half = match endian {
+            Endian::Big => u64::from_be_bytes(word),
+            Endian::Little => u64::from_le_bytes(word),
+        };
+    }
+    halves
+}
+
+/// Compare two byte strings in time independent of where they differ. Only the lengths
+/// (which are public for digests) can short-circuit.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Decode a hex string (either case) into bytes; `None` on odd length or non-hex chars.
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            let hi = (pair[0] as char).to_digit(16)?;
+            let lo = (pair[1] as char).to_digit(16)?;
+            Some((hi * 16 + lo) as u8)
+        })
+        .collect()
+}
+
+/// Encode `bytes` as padded standard-alphabet base64.
+fn encode_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for group in bytes.chunks(3) {
+        let buffer = group.iter().enumerate().fold(0u32, |acc, (i, &byte)| acc | u32::from(byte) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= group.len() {
+                encoded.push(ALPHABET[(buffer >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Decode base64 in either the standard or URL-safe alphabet, padded or unpadded.
+fn decode_base64(input: &str) -> Result<Vec<u8>, JsValue> {
+    let unpadded = input.trim_end_matches('=');
+    let padding = input.len() - unpadded.len();
+    if padding > 2 || (padding > 0 && !input.len().is_multiple_of(4)) || unpadded.len() % 4 == 1 {
+        return Err(js_error("Invalid base64: bad length or padding"));
+    }
+
+    let mut bytes = Vec::with_capacity(unpadded.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for (index, c) in unpadded.bytes().enumerate() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return Err(js_error(&format!("Invalid base64 character {:?} at offset {}", c as char, index))),
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
         }
     }
+    Ok(bytes)
 }
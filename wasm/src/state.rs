@@ -0,0 +1,172 @@
+//! Transparent, serializable MD5 used as the incremental-session hasher.
+//!
+//! The `md-5` crate keeps its chaining registers private, so features that need the raw
+//! `(a, b, c, d)` words or a resumable snapshot of a session (export/import, checkpoints)
+//! run sessions on this implementation instead. Only compiled with the `session-state`
+//! feature; one-shot hashing always uses `md-5`.
+
+use md5::{digest::Output, Md5};
+
+/// Leading bytes of an exported state: magic plus format version.
+const EXPORT_HEADER: [u8; 5] = *b"MD5S\x01";
+
+const INIT: [u32; 4] = [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476];
+
+const SHIFTS: [u32; 64] = [
+    7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22,
+    5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20,
+    4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23,
+    6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+];
+
+const CONSTANTS: [u32; 64] = [
+    0xd76a_a478, 0xe8c7_b756, 0x2420_70db, 0xc1bd_ceee, 0xf57c_0faf, 0x4787_c62a, 0xa830_4613, 0xfd46_9501,
+    0x6980_98d8, 0x8b44_f7af, 0xffff_5bb1, 0x895c_d7be, 0x6b90_1122, 0xfd98_7193, 0xa679_438e, 0x49b4_0821,
+    0xf61e_2562, 0xc040_b340, 0x265e_5a51, 0xe9b6_c7aa, 0xd62f_105d, 0x0244_1453, 0xd8a1_e681, 0xe7d3_fbc8,
+    0x21e1_cde6, 0xc337_07d6, 0xf4d5_0d87, 0x455a_14ed, 0xa9e3_e905, 0xfcef_a3f8, 0x676f_02d9, 0x8d2a_4c8a,
+    0xfffa_3942, 0x8771_f681, 0x6d9d_6122, 0xfde5_380c, 0xa4be_ea44, 0x4bde_cfa9, 0xf6bb_4b60, 0xbebf_bc70,
+    0x289b_7ec6, 0xeaa1_27fa, 0xd4ef_3085, 0x0488_1d05, 0xd9d4_d039, 0xe6db_99e5, 0x1fa2_7cf8, 0xc4ac_5665,
+    0xf429_2244, 0x432a_ff97, 0xab94_23a7, 0xfc93_a039, 0x655b_59c3, 0x8f0c_cc92, 0xffef_f47d, 0x8584_5dd1,
+    0x6fa8_7e4f, 0xfe2c_e6e0, 0xa301_4314, 0x4e08_11a1, 0xf753_7e82, 0xbd3a_f235, 0x2ad7_d2bb, 0xeb86_d391,
+];
+
+#[derive(Clone)]
+pub struct Md5State {
+    state: [u32; 4],
+    buffer: [u8; 64],
+    buffer_len: usize,
+    total_len: u64,
+}
+
+impl Md5State {
+    pub fn new() -> Md5State {
+        Md5State {
+            state: INIT,
+            buffer: [0; 64],
+            buffer_len: 0,
+            total_len: 0,
+        }
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.total_len = self.total_len.wrapping_add(data.len() as u64);
+
+        if self.buffer_len > 0 {
+            let take = (64 - self.buffer_len).min(data.len());
+            self.buffer[self.buffer_len..self.buffer_len + take].copy_from_slice(&data[..take]);
+            self.buffer_len += take;
+            data = &data[take..];
+            if self.buffer_len < 64 {
+                return;
+            }
+            let block = self.buffer;
+            compress(&mut self.state, &block);
+            self.buffer_len = 0;
+        }
+
+        let mut blocks = data.chunks_exact(64);
+        for block in &mut blocks {
+            compress(&mut self.state, block);
+        }
+        let rest = blocks.remainder();
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.buffer_len = rest.len();
+    }
+
+    /// The chaining registers `(a, b, c, d)` after the last complete 64-byte block.
+    pub fn words(&self) -> [u32; 4] {
+        self.state
+    }
+
+    /// Total number of bytes consumed so far.
+    pub fn total_len(&self) -> u64 {
+        self.total_len
+    }
+
+    pub fn finalize(mut self) -> Output<Md5> {
+        let bit_len = self.total_len.wrapping_mul(8);
+        let padding_len = if self.buffer_len < 56 { 56 - self.buffer_len } else { 120 - self.buffer_len };
+        let mut padding = [0u8; 64];
+        padding[0] = 0x80;
+        self.update(&padding[..padding_len]);
+        self.update(&bit_len.to_le_bytes());
+
+        let mut out = Output::<Md5>::default();
+        for (bytes, word) in out.chunks_exact_mut(4).zip(self.state.iter()) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        out
+    }
+
+    /// Serialize as `"MD5S" 0x01 | a b c d (u32 LE) | total_len (u64 LE) | pending bytes`.
+    pub fn export(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(EXPORT_HEADER.len() + 24 + self.buffer_len);
+        out.extend_from_slice(&EXPORT_HEADER);
+        for word in &self.state {
+            out.extend_from_slice(&word.to_le_bytes());
+        }
+        out.extend_from_slice(&self.total_len.to_le_bytes());
+        out.extend_from_slice(&self.buffer[..self.buffer_len]);
+        out
+    }
+
+    /// Inverse of `export`; `None` if the bytes are not a consistent exported state.
+    pub fn import(bytes: &[u8]) -> Option<Md5State> {
+        let body = bytes.strip_prefix(&EXPORT_HEADER[..])?;
+        if body.len() < 24 {
+            return None;
+        }
+        let (fixed, pending) = body.split_at(24);
+
+        let mut state = [0u32; 4];
+        for (word, raw) in state.iter_mut().zip(fixed[..16].chunks_exact(4)) {
+            *word = u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]);
+        }
+        let mut len = [0u8; 8];
+        len.copy_from_slice(&fixed[16..]);
+        let total_len = u64::from_le_bytes(len);
+        if pending.len() >= 64 || total_len % 64 != pending.len() as u64 {
+            return None;
+        }
+
+        let mut buffer = [0u8; 64];
+        buffer[..pending.len()].copy_from_slice(pending);
+        Some(Md5State {
+            state,
+            buffer,
+            buffer_len: pending.len(),
+            total_len,
+        })
+    }
+}
+
+fn compress(state: &mut [u32; 4], block: &[u8]) {
+    let mut m = [0u32; 16];
+    for (word, bytes) in m.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+
+    let [mut a, mut b, mut c, mut d] = *state;
+    for i in 0..64 {
+        let (f, g) = match i / 16 {
+            0 => ((b & c) | (!b & d), i),
+            1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+            2 => (b ^ c ^ d, (3 * i + 5) % 16),
+            _ => (c ^ (b | !d), (7 * i) % 16),
+        };
+        let rotated = a
+            .wrapping_add(f)
+            .wrapping_add(CONSTANTS[i])
+            .wrapping_add(m[g])
+            .rotate_left(SHIFTS[i]);
+        a = d;
+        d = c;
+        c = b;
+        b = b.wrapping_add(rotated);
+    }
+
+    state[0] = state[0].wrapping_add(a);
+    state[1] = state[1].wrapping_add(b);
+    state[2] = state[2].wrapping_add(c);
+    state[3] = state[3].wrapping_add(d);
+}
@@ -0,0 +1,61 @@
+//! Object-owned streaming hasher, an alternative to id-keyed incremental sessions.
+
+use md5::{Digest, Md5};
+use wasm_bindgen::prelude::*;
+
+/// A streaming MD5 owned by its JS object rather than the global session map: there is no
+/// id to collide or leak, and the state is freed when the object is freed (explicitly with
+/// `free()`, or by garbage collection).
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct Md5Stream {
+    /// `None` once finalized; every later call throws instead of starting over.
+    hasher: Option<Md5>,
+}
+
+impl Default for Md5Stream {
+    fn default() -> Self {
+        Md5Stream { hasher: Some(Md5::new()) }
+    }
+}
+
+#[wasm_bindgen]
+impl Md5Stream {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Md5Stream {
+        Md5Stream::default()
+    }
+
+    /// Append `data` to the stream. Throws once the stream is finalized.
+    pub fn update(&mut self, data: &[u8]) -> Result<(), JsValue> {
+        self.live()?.update(data);
+        Ok(())
+    }
+
+    /// Return the digest truncated to `md5_length` hex chars and close the stream: any later
+    /// `update`, `finalize` or `clone` throws "stream already finalized". A `md5_length`
+    /// outside 1..=32 throws and leaves the stream open.
+    pub fn finalize(&mut self, md5_length: usize) -> Result<String, JsValue> {
+        crate::check_md5_length(md5_length)?;
+        let hasher = self.hasher.take().ok_or_else(already_finalized)?;
+        Ok(crate::Md5Calculator::truncate_hash(&format!("{:x}", hasher.finalize()), md5_length))
+    }
+
+    /// Independent copy of the current state, e.g. to digest a prefix and keep streaming.
+    /// Throws once the stream is finalized.
+    #[wasm_bindgen(js_name = clone)]
+    pub fn fork(&mut self) -> Result<Md5Stream, JsValue> {
+        self.live()?;
+        Ok(self.clone())
+    }
+}
+
+impl Md5Stream {
+    fn live(&mut self) -> Result<&mut Md5, JsValue> {
+        self.hasher.as_mut().ok_or_else(already_finalized)
+    }
+}
+
+fn already_finalized() -> JsValue {
+    crate::js_error("stream already finalized")
+}
@@ -0,0 +1,48 @@
+//! Incremental UTF-8 validation for input that arrives in chunks.
+
+/// Validates a byte stream as UTF-8 one chunk at a time, carrying an incomplete multibyte
+/// sequence at the end of a chunk over to the next one.
+#[derive(Default)]
+pub struct Utf8Validator {
+    /// Leading bytes of a sequence split across chunks (at most 3).
+    pending: Vec<u8>,
+    invalid: bool,
+}
+
+impl Utf8Validator {
+    pub fn feed(&mut self, chunk: &[u8]) {
+        if self.invalid {
+            return;
+        }
+
+        let mut rest = chunk;
+        while !self.pending.is_empty() {
+            let (&byte, tail) = match rest.split_first() {
+                Some(split) => split,
+                None => return,
+            };
+            self.pending.push(byte);
+            rest = tail;
+            match std::str::from_utf8(&self.pending) {
+                Ok(_) => self.pending.clear(),
+                Err(error) if error.error_len().is_some() => {
+                    self.invalid = true;
+                    return;
+                }
+                Err(_) => {}
+            }
+        }
+
+        if let Err(error) = std::str::from_utf8(rest) {
+            match error.error_len() {
+                Some(_) => self.invalid = true,
+                None => self.pending.extend_from_slice(&rest[error.valid_up_to()..]),
+            }
+        }
+    }
+
+    /// True if everything fed is valid UTF-8 and does not end mid-sequence.
+    pub fn finish(self) -> bool {
+        !self.invalid && self.pending.is_empty()
+    }
+}
@@ -1,3 +1,14 @@
+#[cfg(feature = "console_error_panic_hook")]
+use std::cell::Cell;
+
+#[cfg(feature = "console_error_panic_hook")]
+thread_local! {
+    /// Id of the incremental session being updated or finalized, for the panic message: the
+    /// address and length of the `&str` borrowed by the innermost `with_active_session`, so
+    /// the hot update path records it without allocating.
+    static ACTIVE_SESSION: Cell<Option<(*const u8, usize)>> = const { Cell::new(None) };
+}
+
 pub fn set_panic_hook() {
     // When the `console_error_panic_hook` feature is enabled, we can call the
     // `set_panic_hook` function at least once during initialization, and then
@@ -5,6 +16,49 @@ pub fn set_panic_hook() {
     //
     // For more details see
     // https://github.com/rustwasm/console_error_panic_hook#readme
+    //
+    // The hook also names the incremental session that was being processed, if any.
     #[cfg(feature = "console_error_panic_hook")]
-    console_error_panic_hook::set_once();
+    {
+        static SET_HOOK: std::sync::Once = std::sync::Once::new();
+        SET_HOOK.call_once(|| {
+            std::panic::set_hook(Box::new(|info| {
+                if let Some(id) = active_session() {
+                    web_sys::console::error_1(&format!("panicked while processing incremental MD5 session: {}", id).into());
+                }
+                console_error_panic_hook::hook(info);
+            }));
+        });
+    }
+}
+
+/// Run `f` with `session_id` recorded as the session being processed. Without the panic
+/// hook nothing reads the record, so `f` just runs.
+#[cfg(feature = "console_error_panic_hook")]
+pub fn with_active_session<R>(session_id: &str, f: impl FnOnce() -> R) -> R {
+    /// Puts the previous record back when `f` returns or unwinds, before `session_id` goes away.
+    struct Restore(Option<(*const u8, usize)>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            ACTIVE_SESSION.with(|active| active.set(self.0));
+        }
+    }
+
+    let _restore = Restore(ACTIVE_SESSION.with(|active| active.replace(Some((session_id.as_ptr(), session_id.len())))));
+    f()
+}
+
+#[cfg(not(feature = "console_error_panic_hook"))]
+pub fn with_active_session<R>(_session_id: &str, f: impl FnOnce() -> R) -> R {
+    f()
+}
+
+#[cfg(feature = "console_error_panic_hook")]
+fn active_session() -> Option<String> {
+    let (ptr, len) = ACTIVE_SESSION.with(Cell::get)?;
+    // SAFETY: the record is only set while `with_active_session` borrows the `&str` it points
+    // to, and its guard restores the previous one before that borrow ends, even on unwind. The
+    // hook runs on this thread during `f`, so the bytes are still that live `str`.
+    let id = unsafe { std::str::from_utf8_unchecked(std::slice::from_raw_parts(ptr, len)) };
+    Some(id.to_owned())
 }
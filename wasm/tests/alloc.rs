@@ -0,0 +1,57 @@
+//! Allocation tests, in their own binary so the counting allocator sees only these calls.
+
+#![cfg(target_arch = "wasm32")]
+
+extern crate wasm_bindgen_test;
+use md5::{Digest, Md5};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use wasm_bindgen_test::*;
+use wasm_md5::Md5Calculator;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// `System`, tracking the bytes currently allocated and their high-water mark.
+struct CountingAllocator;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let live = LIVE.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(live, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Peak bytes allocated on top of what was live when `PEAK` was last reset.
+fn peak_growth_since(baseline: usize) -> usize {
+    PEAK.load(Ordering::Relaxed) - baseline
+}
+
+#[wasm_bindgen_test]
+async fn js_array_input_is_hashed_without_a_full_copy() {
+    let calculator = Md5Calculator::new();
+    let len = 50 * 1024 * 1024;
+    let data = js_sys::Uint8Array::new_with_length(len);
+    data.fill(0x61, 0, len);
+
+    let baseline = LIVE.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    let hex = calculator.calculate_md5_from_js_async(data, 32, None, None).await.unwrap();
+    let growth = peak_growth_since(baseline);
+    assert!(growth < 1024 * 1024, "allocations peaked {} bytes above the baseline", growth);
+    assert_eq!(hex, format!("{:x}", Md5::digest(vec![0x61u8; len as usize])));
+}
@@ -3,11 +3,1860 @@
 #![cfg(target_arch = "wasm32")]
 
 extern crate wasm_bindgen_test;
+use md5::{Digest, Md5};
+use wasm_bindgen::JsValue;
 use wasm_bindgen_test::*;
+use wasm_md5::{hex_to_bytes, DedupIndex, DigestFormat, Endian, LengthRounding, Md5Calculator, Md5Stream, TruncateSide};
 
 wasm_bindgen_test_configure!(run_in_browser);
 
+/// Deterministic pseudo-random bytes (LCG) for tests that need high-entropy input.
+fn patterned_bytes(len: usize, seed: u32) -> Vec<u8> {
+    let mut value = seed;
+    (0..len)
+        .map(|_| {
+            value = value.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (value >> 24) as u8
+        })
+        .collect()
+}
+
+fn get(object: &JsValue, key: &str) -> JsValue {
+    js_sys::Reflect::get(object, &JsValue::from_str(key)).unwrap()
+}
+
 #[wasm_bindgen_test]
 fn pass() {
     assert_eq!(1 + 1, 2);
 }
+
+#[wasm_bindgen_test]
+fn salted_session_matches_manual_salt_prefix() {
+    let calculator = Md5Calculator::new();
+    calculator.start_incremental_md5_salted("salted", b"app-salt").unwrap();
+    assert!(calculator.update_incremental_md5("salted", b"hello "));
+    assert!(calculator.update_incremental_md5("salted", b"world"));
+
+    let expected = format!("{:x}", Md5::digest(b"app-salthello world"));
+    assert_eq!(calculator.finalize_incremental_md5("salted", 32).unwrap(), expected);
+}
+
+#[wasm_bindgen_test]
+fn finalize_incremental_is_synchronous() {
+    let calculator = Md5Calculator::new();
+    calculator.start_incremental_md5("sync").unwrap();
+    assert!(calculator.update_incremental_md5("sync", b"abc"));
+
+    // A plain `String` binding (no `.await`) fails to compile if finalize ever becomes async.
+    let digest: String = calculator.finalize_incremental_md5("sync", 32).unwrap();
+    assert_eq!(digest, "900150983cd24fb0d6963f7d28e17f72");
+}
+
+#[wasm_bindgen_test]
+async fn entropy_estimate_spans_constant_to_random() {
+    let calculator = Md5Calculator::new();
+
+    let zeros = vec![0u8; 64 * 1024];
+    let result = calculator.calculate_md5_with_entropy_async(&zeros, 32).await.unwrap();
+    assert_eq!(get(&result, "hex").as_string().unwrap(), format!("{:x}", Md5::digest(&zeros)));
+    assert!(get(&result, "entropy_bits_per_byte").as_f64().unwrap() < 1e-9);
+
+    let random = patterned_bytes(64 * 1024, 7);
+    let result = calculator.calculate_md5_with_entropy_async(&random, 32).await.unwrap();
+    assert!(get(&result, "entropy_bits_per_byte").as_f64().unwrap() > 7.9);
+}
+
+#[wasm_bindgen_test]
+fn rolling_digest_covers_only_the_last_window() {
+    let calculator = Md5Calculator::new();
+    assert!(calculator.start_rolling_md5("window", 4).unwrap());
+    assert!(calculator.push_rolling_md5("window", b"ab"));
+    assert_eq!(calculator.digest_rolling_md5("window", 32).unwrap(), format!("{:x}", Md5::digest(b"ab")));
+
+    assert!(calculator.push_rolling_md5("window", b"cdef"));
+    assert_eq!(calculator.digest_rolling_md5("window", 32).unwrap(), format!("{:x}", Md5::digest(b"cdef")));
+
+    assert!(calculator.push_rolling_md5("window", b"0123456789"));
+    assert_eq!(calculator.digest_rolling_md5("window", 32).unwrap(), format!("{:x}", Md5::digest(b"6789")));
+    assert!(calculator.cancel_rolling_md5("window"));
+}
+
+#[wasm_bindgen_test]
+async fn truncation_side_selects_leading_or_trailing_chars() {
+    let calculator = Md5Calculator::new();
+    let full = format!("{:x}", Md5::digest(b"legacy-id"));
+
+    let start = calculator.calculate_md5_truncated_async(b"legacy-id", 8, TruncateSide::Start).await.unwrap();
+    let end = calculator.calculate_md5_truncated_async(b"legacy-id", 8, TruncateSide::End).await.unwrap();
+    assert_eq!(start, full[..8]);
+    assert_eq!(end, full[24..]);
+    assert_ne!(start, end);
+}
+
+#[wasm_bindgen_test]
+fn seeded_session_matches_start_then_update() {
+    let calculator = Md5Calculator::new();
+    calculator.start_incremental_md5_from("seeded", b"header|").unwrap();
+    calculator.start_incremental_md5("manual").unwrap();
+    assert!(calculator.update_incremental_md5("manual", b"header|"));
+
+    for id in ["seeded", "manual"] {
+        assert!(calculator.update_incremental_md5(id, b"body"));
+    }
+    assert_eq!(
+        calculator.finalize_incremental_md5("seeded", 32).unwrap(),
+        calculator.finalize_incremental_md5("manual", 32).unwrap()
+    );
+}
+
+#[wasm_bindgen_test]
+async fn lone_surrogate_is_rejected_or_replaced_deterministically() {
+    let calculator = Md5Calculator::new();
+    let lone = js_sys::JsString::from_char_code1(0xD800);
+
+    assert!(calculator.calculate_md5_str_lossless_async(lone.clone(), 32, false).await.is_err());
+
+    let replaced = calculator.calculate_md5_str_lossless_async(lone, 32, true).await.unwrap();
+    assert_eq!(replaced, format!("{:x}", Md5::digest([0xEF, 0xBF, 0xBD])));
+
+    let valid = calculator.calculate_md5_str_lossless_async("héllo".into(), 32, false).await.unwrap();
+    assert_eq!(valid, format!("{:x}", Md5::digest("héllo".as_bytes())));
+}
+
+#[wasm_bindgen_test]
+async fn fingerprint_mapping_is_pinned() {
+    let calculator = Md5Calculator::new();
+    // md5("abc") = 90 01 50 98 ...; changing this output breaks stored fingerprints.
+    assert_eq!(calculator.calculate_md5_fingerprint_async(b"abc").await, "husky-acid-desert-52");
+}
+
+#[wasm_bindgen_test]
+async fn bucket_matches_python_reference_vectors() {
+    let calculator = Md5Calculator::new();
+    // int(hashlib.md5(key).hexdigest()[:16], 16) % buckets
+    assert_eq!(calculator.md5_bucket_async(b"user:42", 16).await.unwrap(), 4);
+    assert_eq!(calculator.md5_bucket_async(b"cache-key", 1000).await.unwrap(), 341);
+    assert!(calculator.md5_bucket_async(b"cache-key", 0).await.is_err());
+}
+
+#[cfg(feature = "session-state")]
+#[wasm_bindgen_test]
+fn exported_state_resumes_to_the_same_digest() {
+    let calculator = Md5Calculator::new();
+    let data = patterned_bytes(1000, 3);
+
+    calculator.start_incremental_md5("original").unwrap();
+    assert!(calculator.update_incremental_md5("original", &data[..333]));
+    let state = calculator.export_incremental_md5("original").unwrap();
+    assert!(calculator.cancel_incremental_md5("original"));
+
+    assert!(calculator.import_incremental_md5("resumed", &state).unwrap());
+    assert!(calculator.update_incremental_md5("resumed", &data[333..]));
+    assert_eq!(calculator.finalize_incremental_md5("resumed", 32).unwrap(), format!("{:x}", Md5::digest(&data)));
+
+    assert!(!calculator.import_incremental_md5("bogus", &state[..state.len() - 1]).unwrap());
+}
+
+#[cfg(feature = "session-state")]
+#[wasm_bindgen_test]
+fn checkpoint_callback_fires_at_byte_intervals() {
+    let calculator = Md5Calculator::new();
+    let seen = js_sys::Array::new();
+    let callback: js_sys::Function = js_sys::Function::new_with_args("seen", "return (state, processed) => seen.push(processed);")
+        .call1(&JsValue::NULL, &seen)
+        .unwrap()
+        .into();
+
+    calculator.start_incremental_md5("checkpointed").unwrap();
+    assert!(calculator.set_checkpoint_callback("checkpointed", 100, callback));
+    for _ in 0..5 {
+        assert!(calculator.update_incremental_md5("checkpointed", &[7u8; 60]));
+    }
+
+    let processed: Vec<f64> = seen.iter().map(|v| v.as_f64().unwrap()).collect();
+    assert_eq!(processed, vec![120.0, 240.0, 300.0]);
+}
+
+#[cfg(feature = "session-state")]
+#[wasm_bindgen_test]
+fn checkpoints_and_interval_digests_share_one_byte_count() {
+    let calculator = Md5Calculator::new();
+    let recorder = js_sys::Function::new_with_args("seen", "return (a, b) => seen.push(typeof a === 'number' ? a : b);");
+    let checkpoints = js_sys::Array::new();
+    let intervals = js_sys::Array::new();
+
+    calculator.start_incremental_md5("counted-source").unwrap();
+    assert!(calculator.update_incremental_md5("counted-source", &[7u8; 60]));
+    let state = calculator.export_incremental_md5("counted-source").unwrap();
+    assert!(calculator.import_incremental_md5("counted", &state).unwrap());
+    assert!(calculator.set_checkpoint_callback("counted", 100, recorder.call1(&JsValue::NULL, &checkpoints).unwrap().into()));
+    assert!(calculator.set_interval_digest_callback("counted", 100, recorder.call1(&JsValue::NULL, &intervals).unwrap().into()));
+    for _ in 0..3 {
+        assert!(calculator.update_incremental_md5("counted", &[7u8; 60]));
+    }
+
+    let processed = |seen: &js_sys::Array| -> Vec<f64> { seen.iter().map(|v| v.as_f64().unwrap()).collect() };
+    assert_eq!(processed(&checkpoints), vec![120.0, 240.0]);
+    assert_eq!(processed(&intervals), processed(&checkpoints));
+    calculator.cancel_incremental_md5("counted-source");
+    calculator.cancel_incremental_md5("counted");
+}
+
+#[wasm_bindgen_test]
+async fn async_iterator_chunks_hash_like_the_concatenation() {
+    let calculator = Md5Calculator::new();
+    let make_iterable = js_sys::Function::new_no_args(
+        "return (async function* () { yield new Uint8Array([97, 98]); yield new Uint8Array([99]); })();",
+    );
+    let iterable = make_iterable.call0(&JsValue::NULL).unwrap();
+    assert_eq!(
+        calculator.calculate_md5_async_iter_async(iterable, 32).await.unwrap(),
+        "900150983cd24fb0d6963f7d28e17f72"
+    );
+
+    let make_bad = js_sys::Function::new_no_args("return (async function* () { yield 'abc'; })();");
+    let bad = make_bad.call0(&JsValue::NULL).unwrap();
+    assert!(calculator.calculate_md5_async_iter_async(bad, 32).await.is_err());
+}
+
+#[wasm_bindgen_test]
+async fn canonical_json_sorts_keys_and_rejects_functions() {
+    let calculator = Md5Calculator::new();
+    let parse = |json: &str| js_sys::JSON::parse(json).unwrap();
+
+    let canonical = r#"{"a":[1,2.5,"x"],"b":{"c":null,"d":true}}"#;
+    let digest = calculator.calculate_md5_json_async(parse(r#"{ "b": { "d": true, "c": null }, "a": [1, 2.5, "x"] }"#), 32).await;
+    assert_eq!(digest.unwrap(), format!("{:x}", Md5::digest(canonical.as_bytes())));
+
+    let with_function = js_sys::Function::new_no_args("return { f: function () {} };").call0(&JsValue::NULL).unwrap();
+    assert!(calculator.calculate_md5_json_async(with_function, 32).await.is_err());
+
+    let cyclic = js_sys::Function::new_no_args("const o = {}; o.self = o; return o;").call0(&JsValue::NULL).unwrap();
+    assert!(calculator.calculate_md5_json_async(cyclic, 32).await.is_err());
+}
+
+#[wasm_bindgen_test]
+async fn canonical_json_rejects_undefined_anywhere() {
+    let calculator = Md5Calculator::new();
+    let build = |body: &str| js_sys::Function::new_no_args(body).call0(&JsValue::NULL).unwrap();
+
+    for value in [
+        JsValue::UNDEFINED,
+        build("return { a: 1, b: undefined };"),
+        build("return { a: [1, undefined] };"),
+        build("return { a: { f() {} } };"),
+    ] {
+        assert!(calculator.calculate_md5_json_async(value, 32).await.is_err());
+    }
+    let nested = calculator.calculate_md5_json_async(build("return { a: { b: [null] } };"), 32).await;
+    assert_eq!(nested.unwrap(), format!("{:x}", Md5::digest(br#"{"a":{"b":[null]}}"#)));
+}
+
+#[wasm_bindgen_test]
+async fn piped_stream_goes_through_the_update_path() {
+    use wasm_bindgen::JsCast;
+
+    let mut calculator = Md5Calculator::new();
+    calculator.set_track_update_sizes(true);
+    let make_source = js_sys::Function::new_no_args(
+        "return new ReadableStream({ start(c) { c.enqueue(new Uint8Array([97, 98])); c.enqueue(new Uint8Array([99])); c.close(); } });",
+    );
+    let source = || -> web_sys::ReadableStream { make_source.call0(&JsValue::NULL).unwrap().unchecked_into() };
+
+    calculator.start_incremental_md5("piped").unwrap();
+    assert!(calculator.pipe_stream_to_session_async("piped", source()).await.unwrap());
+    assert_eq!(calculator.session_update_sizes("piped").unwrap(), vec![2, 1]);
+    assert_eq!(calculator.total_bytes_hashed(), 3);
+    assert_eq!(calculator.finalize_incremental_md5("piped", 32).unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+
+    calculator.start_incremental_md5("piped-gap").unwrap();
+    assert!(calculator.update_incremental_md5_at("piped-gap", 1, b"x"));
+    let error = calculator.pipe_stream_to_session_async("piped-gap", source()).await.unwrap_err();
+    assert_eq!(get(&error, "code").as_string().unwrap(), "chunk_gap");
+    assert!(calculator.pipe_stream_to_session_async("piped-missing", source()).await.is_err());
+}
+
+#[wasm_bindgen_test]
+async fn tee_passes_bytes_through_and_resolves_digest() {
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_futures::JsFuture;
+
+    let calculator = Md5Calculator::new();
+    let make_source = js_sys::Function::new_no_args(
+        "return new ReadableStream({ start(c) { c.enqueue(new Uint8Array([97, 98])); c.enqueue(new Uint8Array([99])); c.close(); } });",
+    );
+    let source: web_sys::ReadableStream = make_source.call0(&JsValue::NULL).unwrap().unchecked_into();
+    let tee = calculator.md5_tee(source).unwrap();
+
+    let stream: web_sys::ReadableStream = get(&tee, "stream").unchecked_into();
+    let reader: web_sys::ReadableStreamDefaultReader = stream.get_reader().unchecked_into();
+    let mut forwarded = Vec::new();
+    loop {
+        let result = JsFuture::from(reader.read()).await.unwrap();
+        if get(&result, "done").is_truthy() {
+            break;
+        }
+        forwarded.extend(get(&result, "value").unchecked_into::<js_sys::Uint8Array>().to_vec());
+    }
+    assert_eq!(forwarded, b"abc");
+
+    let digest = JsFuture::from(get(&tee, "digest_promise").unchecked_into::<js_sys::Promise>()).await.unwrap();
+    assert_eq!(digest.as_string().unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+}
+
+#[wasm_bindgen_test]
+async fn verify_batch_reports_per_index_matches() {
+    let calculator = Md5Calculator::new();
+    let buffers = vec![
+        js_sys::Uint8Array::from(&b"abc"[..]),
+        js_sys::Uint8Array::from(&b"abd"[..]),
+        js_sys::Uint8Array::from(&b""[..]),
+    ];
+    let expected = vec![
+        " 900150983CD24FB0D6963F7D28E17F72 ".to_string(),
+        "900150983cd24fb0d6963f7d28e17f72".to_string(),
+        "not-hex".to_string(),
+    ];
+    let results = calculator.verify_batch_async(buffers.clone(), expected).await.unwrap();
+    let results: Vec<bool> = results.iter().map(|v| v.as_bool().unwrap()).collect();
+    assert_eq!(results, vec![true, false, false]);
+
+    assert!(calculator.verify_batch_async(buffers, vec![String::new()]).await.is_err());
+}
+
+#[wasm_bindgen_test]
+async fn pause_holds_hashing_at_a_yield_point_until_resumed() {
+    let calculator = Md5Calculator::new();
+    let data = patterned_bytes(5 * 1024 * 1024, 11);
+    let done = std::cell::Cell::new(false);
+
+    calculator.pause();
+    let hash = async {
+        let hex = calculator.calculate_md5_async(&data, 32, None, None, None).await.unwrap();
+        done.set(true);
+        hex
+    };
+    let control = async {
+        for _ in 0..10 {
+            let tick = js_sys::Promise::resolve(&JsValue::NULL);
+            wasm_bindgen_futures::JsFuture::from(tick).await.unwrap();
+        }
+        assert!(calculator.is_paused());
+        assert!(!done.get());
+        calculator.resume();
+    };
+    let (hex, ()) = futures::join!(hash, control);
+
+    assert!(done.get());
+    assert_eq!(hex, format!("{:x}", Md5::digest(&data)));
+}
+
+#[wasm_bindgen_test]
+fn pause_is_ignored_by_every_loop_while_yielding_is_disabled() {
+    use futures::FutureExt;
+
+    let mut calculator = Md5Calculator::new();
+    calculator.set_yielding_enabled(false);
+    calculator.set_yield_interval(16).unwrap();
+    calculator.pause();
+    let data = patterned_bytes(4096, 17);
+
+    // Each call must settle on its first poll instead of waiting for `resume`.
+    assert!(calculator.calculate_md5_async(&data, 32, None, None, None).now_or_never().is_some());
+    assert!(calculator.calculate_md5_strided_async(&data, 3, 32).now_or_never().is_some());
+    assert!(calculator.calculate_md5_skip_frames_async(&data, 8, 8, 32).now_or_never().is_some());
+    assert!(calculator.calculate_md5_rle_async(&[b'a', 0, 1, 0, 0], 32).now_or_never().is_some());
+    calculator.resume();
+}
+
+#[wasm_bindgen_test]
+async fn cached_digest_is_written_through_a_fresh_transaction() {
+    use wasm_bindgen::JsCast;
+
+    let calculator = Md5Calculator::new();
+    // Stand-in for IndexedDB: the store handed in belongs to a transaction that has already
+    // committed, so only a store from a new transaction on its database accepts writes.
+    let fixture = js_sys::Function::new_no_args(
+        "const records = [], modes = [];
+        const writable = { keyPath: null, name: 'digests', put(value, key) {
+            records.push([key, value.hex]); const request = {};
+            Promise.resolve().then(() => request.onsuccess({})); return request; } };
+        const db = { transaction(name, mode) { modes.push(name + ':' + mode); return { objectStore: () => writable }; } };
+        const stale = { keyPath: null, name: 'digests', transaction: { db },
+            put() { throw new Error('TransactionInactiveError'); } };
+        return { stale, records, modes };",
+    )
+    .call0(&JsValue::NULL)
+    .unwrap();
+    let store: web_sys::IdbObjectStore = get(&fixture, "stale").unchecked_into();
+
+    let data = patterned_bytes(5 * 1024 * 1024, 21);
+    let hex = calculator.calculate_md5_and_cache_async("upload-1", &data, store, 32).await.unwrap();
+    assert_eq!(hex, format!("{:x}", Md5::digest(&data)));
+    let records = js_sys::Array::from(&get(&fixture, "records"));
+    assert_eq!(records.length(), 1);
+    let record = js_sys::Array::from(&records.get(0));
+    assert_eq!(record.get(0).as_string().unwrap(), "upload-1");
+    assert_eq!(record.get(1).as_string().unwrap(), hex);
+    assert_eq!(js_sys::Array::from(&get(&fixture, "modes")).get(0).as_string().unwrap(), "digests:readwrite");
+}
+
+#[wasm_bindgen_test]
+fn peek_all_lists_sessions_sorted_without_consuming_them() {
+    let calculator = Md5Calculator::new();
+    calculator.start_incremental_md5("peekall-b").unwrap();
+    calculator.start_incremental_md5("peekall-a").unwrap();
+    assert!(calculator.update_incremental_md5("peekall-a", b"ab"));
+
+    // Other tests may leave sessions behind in the shared map; only look at ours.
+    let peeked: Vec<JsValue> = js_sys::Array::from(&calculator.peek_all(32).unwrap())
+        .iter()
+        .filter(|entry| get(entry, "id").as_string().unwrap().starts_with("peekall-"))
+        .collect();
+    let ids: Vec<String> = peeked.iter().map(|entry| get(entry, "id").as_string().unwrap()).collect();
+    assert_eq!(ids, ["peekall-a", "peekall-b"]);
+    assert_eq!(get(&peeked[0], "hex").as_string().unwrap(), format!("{:x}", Md5::digest(b"ab")));
+    assert_eq!(get(&peeked[0], "bytes").as_f64(), Some(2.0));
+    assert_eq!(get(&peeked[1], "hex").as_string().unwrap(), format!("{:x}", Md5::digest(b"")));
+
+    assert!(calculator.update_incremental_md5("peekall-a", b"c"));
+    assert_eq!(calculator.finalize_incremental_md5("peekall-a", 32).unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+    assert_eq!(calculator.finalize_incremental_md5("peekall-b", 32).unwrap(), format!("{:x}", Md5::digest(b"")));
+}
+
+#[wasm_bindgen_test]
+async fn diff_hashes_the_sorted_multiset_difference() {
+    let calculator = Md5Calculator::new();
+    let diff = calculator.calculate_md5_diff_async(b"aabbccd", b"abdz", 32).await.unwrap();
+    assert_eq!(diff, format!("{:x}", Md5::digest(b"abcc")));
+    assert_eq!(calculator.calculate_md5_diff_async(b"abc", b"", 32).await.unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+    assert_eq!(calculator.calculate_md5_diff_async(b"abc", b"abc", 32).await.unwrap(), format!("{:x}", Md5::digest(b"")));
+
+    assert!(calculator.calculate_md5_diff_async(b"ba", b"", 32).await.is_err());
+    assert!(calculator.calculate_md5_diff_async(b"ab", b"ba", 32).await.is_err());
+}
+
+#[wasm_bindgen_test]
+async fn tagged_digest_passes_the_mime_type_through() {
+    let calculator = Md5Calculator::new();
+    let tagged = calculator.calculate_md5_tagged_async(b"abc", "Image/PNG; charset=x", 16).await.unwrap();
+    assert_eq!(get(&tagged, "hex").as_string().unwrap(), "900150983cd24fb0");
+    assert_eq!(get(&tagged, "mime").as_string().unwrap(), "Image/PNG; charset=x");
+    assert_eq!(get(&tagged, "length").as_f64(), Some(3.0));
+
+    let empty = calculator.calculate_md5_tagged_async(b"", "", 32).await.unwrap();
+    assert_eq!(get(&empty, "mime").as_string().unwrap(), "");
+    assert_eq!(get(&empty, "length").as_f64(), Some(0.0));
+}
+
+#[wasm_bindgen_test]
+async fn uuid_v3_matches_rfc_4122_reference() {
+    let calculator = Md5Calculator::new();
+    // Python: uuid.uuid3(uuid.NAMESPACE_DNS, "python.org")
+    let uuid = calculator.calculate_uuid_v3_async("6ba7b810-9dad-11d1-80b4-00c04fd430c8", b"python.org").await;
+    assert_eq!(uuid.unwrap(), "6fa459ea-ee8a-3ca4-894e-db77e160355e");
+
+    assert!(calculator.calculate_uuid_v3_async("6ba7b810-9dad", b"python.org").await.is_err());
+}
+
+#[wasm_bindgen_test]
+fn text_updates_encode_as_utf8_or_latin1() {
+    let calculator = Md5Calculator::new();
+    calculator.start_incremental_md5("utf8").unwrap();
+    assert!(calculator.update_incremental_md5_text("utf8", "café", "UTF-8").unwrap());
+    assert_eq!(calculator.finalize_incremental_md5("utf8", 32).unwrap(), format!("{:x}", Md5::digest("café".as_bytes())));
+
+    calculator.start_incremental_md5("latin1").unwrap();
+    assert!(calculator.update_incremental_md5_text("latin1", "café", "latin1").unwrap());
+    assert_eq!(calculator.finalize_incremental_md5("latin1", 32).unwrap(), format!("{:x}", Md5::digest(b"caf\xe9")));
+
+    calculator.start_incremental_md5("bad").unwrap();
+    assert!(calculator.update_incremental_md5_text("bad", "€", "latin1").is_err());
+    assert!(calculator.update_incremental_md5_text("bad", "abc", "utf-16").is_err());
+    assert!(!calculator.update_incremental_md5_text("missing", "abc", "utf-8").unwrap());
+}
+
+#[wasm_bindgen_test]
+fn incremental_str_updates_hash_the_utf8_bytes() {
+    let calculator = Md5Calculator::new();
+    calculator.start_incremental_md5("str").unwrap();
+    assert!(calculator.update_incremental_md5_str("str", "naïve "));
+    assert!(calculator.update_incremental_md5_str("str", "日本"));
+    assert_eq!(calculator.finalize_incremental_md5("str", 32).unwrap(), format!("{:x}", Md5::digest("naïve 日本".as_bytes())));
+    assert!(!calculator.update_incremental_md5_str("missing", "abc"));
+}
+
+#[wasm_bindgen_test]
+fn expected_yield_count_mirrors_the_chunk_loop() {
+    let mut calculator = Md5Calculator::new();
+    for &len in &[0, 512 * 1024, 2 * 1024 * 1024, 5 * 1024 * 1024 + 7, 10 * 1024 * 1024, 33 * 1024 * 1024 + 1] {
+        let plan = calculator.plan_md5(len);
+        let simulated = if get(&plan, "chunked").is_truthy() {
+            let chunk_size = get(&plan, "chunk_size").as_f64().unwrap() as usize;
+            let mut since_yield = 0;
+            let mut yields = 0;
+            for start in (0..len).step_by(chunk_size) {
+                since_yield += chunk_size.min(len - start);
+                if since_yield >= 2 * 1024 * 1024 {
+                    since_yield = 0;
+                    yields += 1;
+                }
+            }
+            yields
+        } else {
+            0
+        };
+        assert_eq!(calculator.expected_yield_count(len), simulated, "len {}", len);
+    }
+
+    calculator.set_yielding_enabled(false);
+    assert_eq!(calculator.expected_yield_count(33 * 1024 * 1024), 0);
+}
+
+#[wasm_bindgen_test]
+async fn chunk_size_and_yield_interval_are_configurable() {
+    let data: Vec<u8> = (0..600 * 1024).map(|i| (i % 251) as u8).collect();
+    let mut calculator = Md5Calculator::new();
+    let expected = calculator.calculate_md5_async(&data, 32, None, None, None).await.unwrap();
+    assert_eq!(calculator.expected_yield_count(data.len()), 0);
+
+    assert!(calculator.set_chunk_size(0).is_err());
+    assert!(calculator.set_yield_interval(0).is_err());
+    calculator.set_chunk_size(100 * 1024).unwrap();
+    calculator.set_yield_interval(200 * 1024).unwrap();
+    assert_eq!(get(&calculator.plan_md5(data.len()), "chunk_size").as_f64(), Some(102400.0));
+    assert_eq!(calculator.expected_yield_count(data.len()), 3);
+    assert_eq!(calculator.calculate_md5_async(&data, 32, None, None, None).await.unwrap(), expected);
+}
+
+#[wasm_bindgen_test]
+async fn shingles_cover_windows_with_explicit_partial_tail() {
+    let calculator = Md5Calculator::new();
+    let data = b"abcdefg";
+    let hex = |bytes: &[u8]| format!("{:x}", Md5::digest(bytes));
+    let collect = |shingles: Vec<JsValue>| -> Vec<(f64, String)> {
+        shingles
+            .iter()
+            .map(|s| (get(s, "offset").as_f64().unwrap(), get(s, "hex").as_string().unwrap()))
+            .collect()
+    };
+
+    let full = collect(calculator.calculate_shingles_async(data, 3, 2, 32, false).await.unwrap());
+    assert_eq!(full, vec![(0.0, hex(b"abc")), (2.0, hex(b"cde")), (4.0, hex(b"efg"))]);
+
+    let partial = collect(calculator.calculate_shingles_async(data, 4, 3, 32, true).await.unwrap());
+    assert_eq!(partial, vec![(0.0, hex(b"abcd")), (3.0, hex(b"defg"))]);
+
+    let tail = collect(calculator.calculate_shingles_async(data, 4, 2, 32, true).await.unwrap());
+    assert_eq!(tail, vec![(0.0, hex(b"abcd")), (2.0, hex(b"cdef")), (4.0, hex(b"efg"))]);
+
+    assert!(calculator.calculate_shingles_async(data, 3, 0, 32, false).await.is_err());
+}
+
+#[wasm_bindgen_test]
+async fn calculate_md5_resolves_or_rejects_with_structured_objects() {
+    let calculator = Md5Calculator::new();
+    let ok = calculator.calculate_md5(b"abc", 16).await.unwrap();
+    assert_eq!(get(&ok, "ok"), JsValue::TRUE);
+    assert_eq!(get(&ok, "hex").as_string().unwrap(), "900150983cd24fb0");
+
+    let err = calculator.calculate_md5(b"abc", 33).await.unwrap_err();
+    assert_eq!(get(&err, "ok"), JsValue::FALSE);
+    assert!(get(&err, "error").as_string().unwrap().contains("md5_length"));
+}
+
+#[wasm_bindgen_test]
+async fn merkle_root_pins_tree_shape_for_one_to_four_leaves() {
+    let calculator = Md5Calculator::new();
+    let leaves: Vec<Vec<u8>> = (0..4u8).map(|i| Md5::digest([i]).to_vec()).collect();
+    let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+    let parent = |a: &[u8], b: &[u8]| Md5::new().chain_update(a).chain_update(b).finalize().to_vec();
+    let root = |count: usize| {
+        let digests = leaves[..count].iter().map(|leaf| hex(leaf)).collect();
+        calculator.calculate_merkle_root_async(digests)
+    };
+
+    let ab = parent(&leaves[0], &leaves[1]);
+    let cc = parent(&leaves[2], &leaves[2]);
+    let cd = parent(&leaves[2], &leaves[3]);
+    assert_eq!(root(1).await.unwrap(), hex(&leaves[0]));
+    assert_eq!(root(2).await.unwrap(), hex(&ab));
+    assert_eq!(root(3).await.unwrap(), hex(&parent(&ab, &cc)));
+    assert_eq!(root(4).await.unwrap(), hex(&parent(&ab, &cd)));
+
+    assert!(calculator.calculate_merkle_root_async(Vec::new()).await.is_err());
+    assert!(calculator.calculate_merkle_root_async(vec!["abc".to_string()]).await.is_err());
+}
+
+#[wasm_bindgen_test]
+fn view_updates_hash_bytes_read_from_wasm_memory() {
+    let calculator = Md5Calculator::new();
+    let data = b"hello wasm memory".to_vec();
+    calculator.start_incremental_md5("view").unwrap();
+    // SAFETY: `data` is neither moved nor reallocated while the view is in use. The view is
+    // made after starting the session, whose allocation could grow (and detach) memory.
+    let view = unsafe { js_sys::Uint8Array::view(&data) };
+    assert!(calculator.update_incremental_md5_view("view", &view).unwrap());
+    assert_eq!(calculator.finalize_incremental_md5("view", 32).unwrap(), format!("{:x}", Md5::digest(&data)));
+
+    calculator.start_incremental_md5("copy").unwrap();
+    let copied = js_sys::Uint8Array::from(&data[..]);
+    assert!(calculator.update_incremental_md5_view("copy", &copied).is_err());
+}
+
+#[wasm_bindgen_test]
+async fn chunked_hashing_matches_reference_across_sizes_and_policies() {
+    // Edge sizes around the chunk loop's boundaries, plus seeded random sizes.
+    let mut sizes = vec![0, 1, 512 * 1024, 512 * 1024 + 1, 2 * 1024 * 1024, 10 * 1024 * 1024, 10 * 1024 * 1024 + 1];
+    let mut seed = 0x5eed_u32;
+    for _ in 0..6 {
+        seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        sizes.push((seed >> 9) as usize % (12 * 1024 * 1024));
+    }
+
+    let mut calculators = vec![Md5Calculator::new(), Md5Calculator::new(), Md5Calculator::new()];
+    calculators[1].set_yielding_enabled(false);
+    calculators[2].set_max_block_ms(0.001);
+
+    for (i, &len) in sizes.iter().enumerate() {
+        let data = patterned_bytes(len, i as u32);
+        let expected = format!("{:x}", Md5::digest(&data));
+        for calculator in &calculators {
+            assert_eq!(calculator.calculate_md5_async(&data, 32, None, None, None).await.unwrap(), expected, "len {}", len);
+        }
+    }
+}
+
+#[wasm_bindgen_test]
+async fn derived_inputs_hash_alike_under_every_chunk_policy() {
+    use std::io::Write;
+
+    let data = patterned_bytes(1024 * 1024 + 333, 21);
+    let other = patterned_bytes(data.len(), 22);
+    let mut sorted = data.clone();
+    sorted.sort_unstable();
+    let mut sorted_other = other[..300_000].to_vec();
+    sorted_other.sort_unstable();
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    encoder.write_all(&data).unwrap();
+    let gzipped = encoder.finish().unwrap();
+    let runs = [[1, 0x21, 0xae, 0x0a, 0], [2, 0, 0, 0, 0], [3, 0x13, 0xa1, 0x07, 0]].concat();
+    let pattern: Vec<u8> = (0..200).map(|i| i % 2).collect();
+
+    // Calculator 0 processes each input in one unbounded step: the reference.
+    let mut calculators = vec![Md5Calculator::new(), Md5Calculator::new(), Md5Calculator::new(), Md5Calculator::new()];
+    calculators[0].set_yielding_enabled(false);
+    calculators[2].set_chunk_size(4099).unwrap();
+    calculators[2].set_yield_interval(10_007).unwrap();
+    calculators[3].set_max_block_ms(0.001);
+
+    let mut results = Vec::new();
+    for calculator in &calculators {
+        let shingles: Vec<String> = calculator
+            .calculate_shingles_async(&data, 100_000, 70_001, 32, true)
+            .await
+            .unwrap()
+            .iter()
+            .map(|shingle| get(shingle, "hex").as_string().unwrap())
+            .collect();
+        results.push(vec![
+            calculator.calculate_md5_gunzip_async(&gzipped, 32).await.unwrap(),
+            calculator.calculate_md5_interleaved_async(&data, &other, &pattern, 10_007, 32).await.unwrap(),
+            calculator.calculate_md5_xor_async(&data, &other, 32).await.unwrap(),
+            calculator.calculate_md5_diff_async(&sorted, &sorted_other, 32).await.unwrap(),
+            calculator.calculate_md5_reversed_async(&data, 32).await.unwrap(),
+            calculator.calculate_md5_strided_async(&data, 7, 32).await.unwrap(),
+            calculator.calculate_md5_skip_frames_async(&data, 1000, 77, 32).await.unwrap(),
+            calculator.calculate_md5_rle_async(&runs, 32).await.unwrap(),
+            shingles.join(","),
+        ]);
+    }
+    for (policy, result) in results.iter().enumerate().skip(1) {
+        assert_eq!(result, &results[0], "policy {}", policy);
+    }
+
+    let reference = &results[0];
+    assert_eq!(reference[0], format!("{:x}", Md5::digest(&data)));
+    let xored: Vec<u8> = data.iter().zip(&other).map(|(x, y)| x ^ y).collect();
+    assert_eq!(reference[2], format!("{:x}", Md5::digest(&xored)));
+    let reversed: Vec<u8> = data.iter().rev().copied().collect();
+    assert_eq!(reference[4], format!("{:x}", Md5::digest(&reversed)));
+    let strided: Vec<u8> = data.iter().step_by(7).copied().collect();
+    assert_eq!(reference[5], format!("{:x}", Md5::digest(&strided)));
+    let content: Vec<u8> = data.chunks(1077).flat_map(|frame| &frame[..frame.len().min(1000)]).copied().collect();
+    assert_eq!(reference[6], format!("{:x}", Md5::digest(&content)));
+    let expanded = [vec![1u8; 0x0aae21], vec![3u8; 0x07a113]].concat();
+    assert_eq!(reference[7], format!("{:x}", Md5::digest(&expanded)));
+}
+
+#[wasm_bindgen_test]
+fn reset_drops_only_this_instances_sessions() {
+    let mut mine = Md5Calculator::new();
+    let theirs = Md5Calculator::new();
+    mine.set_log_enabled(true);
+    mine.start_incremental_md5("reset-mine").unwrap();
+    assert!(mine.start_rolling_md5("reset-rolling", 4).unwrap());
+    theirs.start_incremental_md5("reset-theirs").unwrap();
+    mine.start_incremental_md5("reset-taken").unwrap();
+    assert!(theirs.start_incremental_md5("reset-taken").unwrap());
+
+    mine.reset();
+    assert!(!mine.is_log_enabled());
+    assert!(!mine.update_incremental_md5("reset-mine", b"x"));
+    assert!(!mine.push_rolling_md5("reset-rolling", b"x"));
+    assert!(theirs.update_incremental_md5("reset-theirs", b"x"));
+    assert!(theirs.update_incremental_md5("reset-taken", b"x"));
+}
+
+#[wasm_bindgen_test]
+async fn colon_formats_group_truncated_hex_into_pairs() {
+    let calculator = Md5Calculator::new();
+    let colon = calculator.calculate_md5_formatted_async(b"abc", 32, DigestFormat::HexColon).await.unwrap();
+    assert_eq!(colon, "90:01:50:98:3c:d2:4f:b0:d6:96:3f:7d:28:e1:7f:72");
+    let upper = calculator.calculate_md5_formatted_async(b"abc", 7, DigestFormat::HexColonUpper).await.unwrap();
+    assert_eq!(upper, "90:01:50:9");
+}
+
+#[wasm_bindgen_test]
+async fn uppercase_hex_truncates_like_upper_casing_the_full_digest() {
+    let calculator = Md5Calculator::new();
+    let full = format!("{:x}", Md5::digest(b"abc"));
+    for &len in &[16, 32, 7] {
+        let expected = full[..len].to_ascii_uppercase();
+        assert_eq!(calculator.calculate_md5_formatted_async(b"abc", len, DigestFormat::HexUpper).await.unwrap(), expected);
+        let both = calculator.calculate_md5_both_case_async(b"abc", len).await.unwrap();
+        assert_eq!(get(&both, "upper").as_string().unwrap(), expected);
+    }
+}
+
+#[wasm_bindgen_test]
+async fn base64_output_encodes_the_raw_digest() {
+    let calculator = Md5Calculator::new();
+    assert_eq!(calculator.calculate_md5_base64_async(b"").await, "1B2M2Y8AsgTpgAmY7PhCfg==");
+    assert_eq!(calculator.calculate_md5_base64_async(b"abc").await, "kAFQmDzST7DWlj99KOF/cg==");
+}
+
+#[wasm_bindgen_test]
+async fn length_rounding_policies_define_non_standard_lengths() {
+    let mut calculator = Md5Calculator::new();
+    let cases: &[(LengthRounding, &[(usize, usize)])] = &[
+        (LengthRounding::Exact, &[(7, 7), (16, 16), (20, 20), (31, 31), (32, 32)]),
+        (LengthRounding::RoundDownToByte, &[(7, 6), (16, 16), (17, 16), (20, 16), (31, 16), (32, 32)]),
+        (LengthRounding::RoundUpToByte, &[(7, 8), (16, 16), (17, 32), (20, 32), (31, 32), (32, 32)]),
+    ];
+    for &(rounding, lengths) in cases {
+        calculator.set_length_rounding(rounding);
+        for &(requested, expected) in lengths {
+            let hex = calculator.calculate_md5_async(b"abc", requested, None, None, None).await.unwrap();
+            assert_eq!(hex, &"900150983cd24fb0d6963f7d28e17f72"[..expected], "{:?} {}", rounding, requested);
+        }
+    }
+}
+
+#[wasm_bindgen_test]
+fn md5_stream_clone_forks_independent_state() {
+    let mut stream = Md5Stream::new();
+    stream.update(b"ab").unwrap();
+    let mut fork = stream.fork().unwrap();
+    stream.update(b"c").unwrap();
+    fork.update(b"d").unwrap();
+    assert_eq!(stream.finalize(32).unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+    assert_eq!(fork.finalize(32).unwrap(), format!("{:x}", Md5::digest(b"abd")));
+}
+
+#[wasm_bindgen_test]
+fn md5_stream_rejects_use_after_finalize() {
+    let mut stream = Md5Stream::new();
+    stream.update(b"abc").unwrap();
+    stream.finalize(32).unwrap();
+
+    let error = js_sys::Error::from(stream.update(b"more").unwrap_err());
+    assert_eq!(error.message(), "stream already finalized");
+    assert!(stream.finalize(32).is_err());
+    assert!(stream.fork().is_err());
+}
+
+#[wasm_bindgen_test]
+fn md5_stream_rejects_bad_lengths_without_closing() {
+    let mut stream = Md5Stream::new();
+    stream.update(b"abc").unwrap();
+    for invalid in [0, 33] {
+        let error = js_sys::Error::from(stream.finalize(invalid).unwrap_err());
+        assert_eq!(error.message(), format!("md5_length must be between 1 and 32, got {}", invalid));
+    }
+    assert_eq!(stream.finalize(32).unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+}
+
+#[wasm_bindgen_test]
+async fn reversed_hash_matches_manually_reversed_input() {
+    let calculator = Md5Calculator::new();
+    let data = patterned_bytes(3 * 1024 * 1024 + 123, 5);
+    let reversed: Vec<u8> = data.iter().rev().copied().collect();
+    assert_eq!(calculator.calculate_md5_reversed_async(&data, 32).await.unwrap(), format!("{:x}", Md5::digest(&reversed)));
+}
+
+#[wasm_bindgen_test]
+fn combined_digests_match_s3_multipart_etag_math() {
+    let calculator = Md5Calculator::new();
+    // Python: md5(md5(b"part-one").digest().unwrap() + md5(b"part-two").digest().unwrap()).hexdigest()
+    let parts = vec!["dede9db222ee612853f44e6e6b1ca792".to_string(), "093064BEE9FA39309997041A6E03F29F".to_string()];
+    assert_eq!(calculator.combine_digests(parts, 32).unwrap(), "4e0f3bff4d24863cbef6c3d3dc5c2b7e");
+    assert!(calculator.combine_digests(vec!["xyz".to_string()], 32).is_err());
+}
+
+#[cfg(feature = "session-state")]
+#[wasm_bindgen_test]
+fn drain_sessions_snapshots_and_clears_every_session() {
+    let calculator = Md5Calculator::new();
+    calculator.start_incremental_md5("drain-b").unwrap();
+    calculator.start_incremental_md5("drain-a").unwrap();
+    assert!(calculator.update_incremental_md5("drain-a", b"abc"));
+    assert!(calculator.start_rolling_md5("drain-rolling", 4).unwrap());
+    calculator.start_incremental_md5("drain-gap").unwrap();
+    assert!(calculator.update_incremental_md5_at("drain-gap", 1, b"b"));
+
+    // Other tests may leave sessions behind in the shared map; only look at ours.
+    let drained: Vec<JsValue> = js_sys::Array::from(&calculator.drain_sessions(32).unwrap())
+        .iter()
+        .filter(|entry| get(entry, "id").as_string().unwrap().starts_with("drain-"))
+        .collect();
+    assert_eq!(drained.len(), 2);
+    let first = drained[0].clone();
+    assert_eq!(get(&first, "id").as_string().unwrap(), "drain-a");
+    assert_eq!(get(&first, "bytes").as_f64().unwrap(), 3.0);
+    assert_eq!(get(&first, "hex").as_string().unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+    assert!(!calculator.update_incremental_md5("drain-b", b"x"));
+    assert!(calculator.cancel_rolling_md5("drain-rolling"));
+    assert!(calculator.update_incremental_md5_at("drain-gap", 0, b"a"));
+    assert_eq!(calculator.finalize_incremental_md5("drain-gap", 32).unwrap(), format!("{:x}", Md5::digest(b"ab")));
+
+    let state = js_sys::Uint8Array::from(get(&first, "state")).to_vec();
+    assert!(calculator.import_incremental_md5("drain-a", &state).unwrap());
+    assert_eq!(calculator.finalize_incremental_md5("drain-a", 32).unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+}
+
+#[wasm_bindgen_test]
+async fn padded_hash_matches_manually_zero_padded_input() {
+    let calculator = Md5Calculator::new();
+    for &(len, padded_len) in &[(0, 0), (1, 512), (511, 512), (512, 512), (513, 1024), (10_000, 10_240)] {
+        let data = patterned_bytes(len, 3);
+        let mut padded = data.clone();
+        padded.resize(padded_len, 0);
+        let hex = calculator.calculate_md5_padded_async(&data, 512, 32).await.unwrap();
+        assert_eq!(hex, format!("{:x}", Md5::digest(&padded)), "len {}", len);
+    }
+    assert!(calculator.calculate_md5_padded_async(b"abc", 0, 32).await.is_err());
+}
+
+#[wasm_bindgen_test]
+async fn byte_count_tallies_target_bytes_across_chunks() {
+    let calculator = Md5Calculator::new();
+    let mut data = vec![7u8; 3 * 1024 * 1024];
+    for i in (0..data.len()).step_by(1000) {
+        data[i] = 0;
+    }
+    let result = calculator.calculate_md5_with_byte_count_async(&data, 0, 32).await.unwrap();
+    assert_eq!(get(&result, "count").as_f64().unwrap(), data.len().div_ceil(1000) as f64);
+    assert_eq!(get(&result, "hex").as_string().unwrap(), format!("{:x}", Md5::digest(&data)));
+}
+
+#[wasm_bindgen_test]
+fn dedup_index_reports_only_novel_chunks() {
+    let mut index = DedupIndex::new();
+    assert!(index.is_empty());
+    let novel: Vec<bool> = [&b"a"[..], b"b", b"a", b"", b"b", b""].iter().map(|chunk| index.insert_if_new(chunk)).collect();
+    assert_eq!(novel, vec![true, true, false, true, false, false]);
+    assert_eq!(index.len(), 3);
+}
+
+#[wasm_bindgen_test]
+async fn base64_input_decodes_both_alphabets_with_optional_padding() {
+    let calculator = Md5Calculator::new();
+    let data = [0xfbu8, 0xff, 0xfe, b'a'];
+    let expected = format!("{:x}", Md5::digest(data));
+    for encoded in &["+//+YQ==", "-__-YQ==", "-__-YQ"] {
+        assert_eq!(calculator.calculate_md5_from_base64_async(encoded, 32).await.unwrap(), expected, "{}", encoded);
+    }
+    for bad in &["+//+Y", "+//+YQ=", "+//+ YQ==", "YQ==="] {
+        assert!(calculator.calculate_md5_from_base64_async(bad, 32).await.is_err(), "{}", bad);
+    }
+}
+
+#[wasm_bindgen_test]
+async fn nobom_hash_skips_only_a_leading_bom() {
+    let calculator = Md5Calculator::new();
+    let abc = "900150983cd24fb0d6963f7d28e17f72";
+    assert_eq!(calculator.calculate_md5_text_nobom_async(b"\xEF\xBB\xBFabc", 32).await.unwrap(), abc);
+    assert_eq!(calculator.calculate_md5_text_nobom_async(b"abc", 32).await.unwrap(), abc);
+    let inner = b"a\xEF\xBB\xBFbc";
+    assert_eq!(calculator.calculate_md5_text_nobom_async(inner, 32).await.unwrap(), format!("{:x}", Md5::digest(inner)));
+}
+
+#[wasm_bindgen_test]
+async fn sortkey_is_base32hex_and_sorts_like_the_digest_bytes() {
+    let calculator = Md5Calculator::new();
+    // Python: base64.b32hexencode(md5(b"abc").digest().unwrap()).rstrip(b"=")
+    assert_eq!(calculator.calculate_md5_sortkey_async(b"abc").await, "I00L161SQ97R1LKM7TUIHOBVE8");
+
+    let mut pairs = Vec::new();
+    for i in 0..32u8 {
+        pairs.push((Md5::digest([i]).to_vec(), calculator.calculate_md5_sortkey_async(&[i]).await));
+    }
+    let mut by_digest = pairs.clone();
+    by_digest.sort_by(|a, b| a.0.cmp(&b.0));
+    pairs.sort_by(|a, b| a.1.cmp(&b.1));
+    assert_eq!(pairs, by_digest);
+}
+
+#[wasm_bindgen_test]
+fn strict_finalize_reports_lost_race_explicitly() {
+    let calculator = Md5Calculator::new();
+    calculator.start_incremental_md5("strict").unwrap();
+    assert!(calculator.update_incremental_md5("strict", b"abc"));
+
+    let first = calculator.finalize_incremental_md5_strict("strict", 32);
+    let second = calculator.finalize_incremental_md5_strict("strict", 32);
+    assert_eq!(first.unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+    assert_eq!(get(&second.unwrap_err(), "code").as_string().unwrap(), "already_finalizing");
+
+    let missing = calculator.finalize_incremental_md5_strict("strict-never-started", 32);
+    assert_eq!(get(&missing.unwrap_err(), "code").as_string().unwrap(), "not_found");
+
+    calculator.start_incremental_md5("strict").unwrap();
+    assert!(calculator.finalize_incremental_md5_strict("strict", 32).is_ok());
+}
+
+#[wasm_bindgen_test]
+async fn strided_hash_samples_every_nth_byte() {
+    let calculator = Md5Calculator::new();
+    let data = patterned_bytes(5 * 1024 * 1024 + 17, 9);
+    for &stride in &[1usize, 3, 1000, 7 * 1024 * 1024] {
+        let sampled: Vec<u8> = data.iter().step_by(stride).copied().collect();
+        let hex = calculator.calculate_md5_strided_async(&data, stride, 32).await.unwrap();
+        assert_eq!(hex, format!("{:x}", Md5::digest(&sampled)), "stride {}", stride);
+    }
+    assert!(calculator.calculate_md5_strided_async(&data, 0, 32).await.is_err());
+}
+
+#[wasm_bindgen_test]
+async fn endian_selects_how_digest_bytes_become_integers() {
+    let calculator = Md5Calculator::new();
+    // md5("abc") = 900150983cd24fb0 d6963f7d28e17f72
+    let big = calculator.calculate_md5_u64x2_async(b"abc", Endian::Big).await;
+    let little = calculator.calculate_md5_u64x2_async(b"abc", Endian::Little).await;
+    assert_eq!(big, vec![0x9001_5098_3cd2_4fb0, 0xd696_3f7d_28e1_7f72]);
+    assert_eq!(little, vec![0xb04f_d23c_9850_0190, 0x727f_e128_7d3f_96d6]);
+
+    let buckets = 1_000_003;
+    assert_eq!(calculator.md5_bucket_endian_async(b"abc", buckets, Endian::Big).await.unwrap() as u64, big[0] % 1_000_003);
+    assert_eq!(calculator.md5_bucket_endian_async(b"abc", buckets, Endian::Little).await.unwrap() as u64, little[0] % 1_000_003);
+}
+
+#[wasm_bindgen_test]
+async fn until_delimiter_reports_consumed_bytes() {
+    let calculator = Md5Calculator::new();
+    let framed = calculator.calculate_md5_until_async(b"abc\nrest\n", b'\n', 32).await.unwrap();
+    assert_eq!(get(&framed, "consumed").as_f64().unwrap(), 3.0);
+    assert_eq!(get(&framed, "hex").as_string().unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+
+    let unframed = calculator.calculate_md5_until_async(b"abc", b'\n', 32).await.unwrap();
+    assert_eq!(get(&unframed, "consumed").as_f64().unwrap(), 3.0);
+    let leading = calculator.calculate_md5_until_async(b"\nabc", b'\n', 32).await.unwrap();
+    assert_eq!(get(&leading, "consumed").as_f64().unwrap(), 0.0);
+}
+
+#[wasm_bindgen_test]
+async fn blob_reads_are_retried_after_a_transient_failure() {
+    use wasm_bindgen::JsCast;
+
+    // A Blob-like mock whose first arrayBuffer() call rejects.
+    let make_flaky = js_sys::Function::new_no_args(
+        "let calls = 0; const bytes = new TextEncoder().encode('abc'); return {
+            size: bytes.length,
+            slice(start, end) {
+                return { arrayBuffer: () => calls++ === 0 ? Promise.reject(new Error('flaky')) : Promise.resolve(bytes.slice(start, end).buffer) };
+            },
+        };",
+    );
+    let flaky = || make_flaky.call0(&JsValue::NULL).unwrap().unchecked_into::<web_sys::Blob>();
+
+    let mut calculator = Md5Calculator::new();
+    assert!(calculator.hash_blob_async(flaky(), 2, 32).await.is_err());
+    calculator.set_read_retries(1);
+    assert_eq!(calculator.hash_blob_async(flaky(), 2, 32).await.unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+}
+
+#[wasm_bindgen_test]
+async fn total_bytes_hashed_accumulates_across_calls() {
+    let mut calculator = Md5Calculator::new();
+    assert_eq!(calculator.total_bytes_hashed(), 0);
+
+    calculator.calculate_md5_async(b"abc", 32, None, None, None).await.unwrap();
+    calculator.start_incremental_md5("total-bytes").unwrap();
+    assert!(calculator.update_incremental_md5("total-bytes", b"hello"));
+    assert!(!calculator.update_incremental_md5("total-bytes-missing", b"ignored"));
+    calculator.finalize_incremental_md5("total-bytes", 32).unwrap();
+    assert_eq!(calculator.total_bytes_hashed(), 8);
+
+    // Paths that hash a derived byte sequence count the bytes actually hashed.
+    calculator.calculate_md5_strided_async(b"abcdef", 2, 32).await.unwrap();
+    assert_eq!(calculator.total_bytes_hashed(), 11);
+    calculator.calculate_md5_skip_frames_async(b"abXcdX", 2, 1, 32).await.unwrap();
+    assert_eq!(calculator.total_bytes_hashed(), 15);
+    calculator.calculate_md5_rle_async(&[b'z', 5, 0, 0, 0], 32).await.unwrap();
+    assert_eq!(calculator.total_bytes_hashed(), 20);
+
+    calculator.reset();
+    assert_eq!(calculator.total_bytes_hashed(), 20);
+}
+
+#[wasm_bindgen_test]
+async fn total_bytes_hashed_counts_hand_rolled_loops() {
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_futures::JsFuture;
+
+    let calculator = Md5Calculator::new();
+    let mut expected = 0;
+    let mut check = |calculator: &Md5Calculator, hashed: u64| {
+        expected += hashed;
+        assert_eq!(calculator.total_bytes_hashed(), expected);
+    };
+
+    let gzipped = [31, 139, 8, 0, 0, 0, 0, 0, 2, 3, 75, 76, 74, 6, 0, 194, 65, 36, 53, 3, 0, 0, 0];
+    calculator.calculate_md5_gunzip_async(&gzipped, 32).await.unwrap();
+    check(&calculator, 3);
+    calculator.calculate_md5_interleaved_async(b"abcd", b"xy", &[0, 1, 0], 2, 32).await.unwrap();
+    check(&calculator, 6);
+    calculator.calculate_md5_xor_async(b"abcd", b"wxyz", 32).await.unwrap();
+    check(&calculator, 4);
+    calculator.calculate_md5_diff_async(b"aabbccd", b"abdz", 32).await.unwrap();
+    check(&calculator, 4);
+    calculator.calculate_md5_reversed_async(b"abcde", 32).await.unwrap();
+    check(&calculator, 5);
+    // Overlapping windows hash their shared bytes again: 0..3, 2..5, 4..7.
+    calculator.calculate_shingles_async(b"abcdefg", 3, 2, 32, false).await.unwrap();
+    check(&calculator, 9);
+    // The outer MD5 hashes the 32 hex chars of the inner digest.
+    calculator.calculate_md5_double_async(b"abc", 32).await.unwrap();
+    check(&calculator, 3 + 32);
+
+    let iterable = js_sys::Function::new_no_args(
+        "return (async function* () { yield new Uint8Array([97, 98]); yield new Uint8Array([99]); })();",
+    )
+    .call0(&JsValue::NULL)
+    .unwrap();
+    calculator.calculate_md5_async_iter_async(iterable, 32).await.unwrap();
+    check(&calculator, 3);
+
+    let source: web_sys::ReadableStream = js_sys::Function::new_no_args(
+        "return new ReadableStream({ start(c) { c.enqueue(new Uint8Array([97, 98])); c.enqueue(new Uint8Array([99])); c.close(); } });",
+    )
+    .call0(&JsValue::NULL)
+    .unwrap()
+    .unchecked_into();
+    let tee = calculator.md5_tee(source).unwrap();
+    let reader: web_sys::ReadableStreamDefaultReader = get(&tee, "stream").unchecked_into::<web_sys::ReadableStream>().get_reader().unchecked_into();
+    while !get(&JsFuture::from(reader.read()).await.unwrap(), "done").is_truthy() {}
+    JsFuture::from(get(&tee, "digest_promise").unchecked_into::<js_sys::Promise>()).await.unwrap();
+    check(&calculator, 3);
+}
+
+#[wasm_bindgen_test]
+async fn aborted_hash_counts_only_the_bytes_it_fed() {
+    use wasm_bindgen::JsCast;
+
+    let calculator = Md5Calculator::new();
+    let data = patterned_bytes(8 * 1024 * 1024, 14);
+    let wiring = js_sys::Function::new_no_args(
+        "const controller = new AbortController();
+        return { signal: controller.signal, onProgress: () => controller.abort() };",
+    )
+    .call0(&JsValue::NULL)
+    .unwrap();
+    let signal: web_sys::AbortSignal = get(&wiring, "signal").unchecked_into();
+    let on_progress: js_sys::Function = get(&wiring, "onProgress").unchecked_into();
+
+    assert_eq!(calculator.calculate_md5_async(&data, 32, None, Some(on_progress), Some(signal)).await.unwrap(), "");
+    let hashed = calculator.total_bytes_hashed();
+    assert!(hashed > 0 && hashed < data.len() as u64, "counted {} of {} bytes", hashed, data.len());
+}
+
+#[wasm_bindgen_test]
+async fn utf8_check_reports_validity_across_chunk_boundaries() {
+    let calculator = Md5Calculator::new();
+    let is_utf8 = |result: &JsValue| get(result, "is_utf8").as_bool().unwrap();
+
+    let valid = calculator.calculate_md5_with_utf8_check_async("héllo wörld".as_bytes(), 32).await.unwrap();
+    assert!(is_utf8(&valid));
+    assert_eq!(get(&valid, "hex").as_string().unwrap(), calculator.calculate_md5_async("héllo wörld".as_bytes(), 32, None, None, None).await.unwrap());
+    assert!(!is_utf8(&calculator.calculate_md5_with_utf8_check_async(b"abc\xff", 32).await.unwrap()));
+    assert!(!is_utf8(&calculator.calculate_md5_with_utf8_check_async(b"abc\xe2\x82", 32).await.unwrap()));
+
+    // Large enough to be chunked at 128 KiB, with a 3-byte sequence straddling the first boundary.
+    let mut split = vec![b'a'; 128 * 1024 - 1];
+    split.extend_from_slice("€".as_bytes());
+    split.resize(600 * 1024, b'a');
+    assert!(is_utf8(&calculator.calculate_md5_with_utf8_check_async(&split, 32).await.unwrap()));
+    split[128 * 1024] = b'a';
+    assert!(!is_utf8(&calculator.calculate_md5_with_utf8_check_async(&split, 32).await.unwrap()));
+}
+
+#[wasm_bindgen_test]
+async fn transform_normalizes_chunks_before_hashing() {
+    let calculator = Md5Calculator::new();
+    let strip_cr = js_sys::Function::new_with_args("chunk", "return chunk.filter((byte) => byte !== 13);");
+    let hex = calculator.calculate_md5_transform_async(b"a\r\nb\r\n", 32, strip_cr).await.unwrap();
+    assert_eq!(hex, calculator.calculate_md5_async(b"a\nb\n", 32, None, None, None).await.unwrap());
+
+    let wrong_type = js_sys::Function::new_with_args("chunk", "return 'not bytes';");
+    assert!(calculator.calculate_md5_transform_async(b"abc", 32, wrong_type).await.is_err());
+}
+
+#[wasm_bindgen_test]
+async fn bitreversed_hashes_each_byte_with_reversed_bits() {
+    let calculator = Md5Calculator::new();
+    let hex = calculator.calculate_md5_bitreversed_async(&[0x01, 0x80, 0x0f, 0xa5, 0xff, 0x00], 32).await.unwrap();
+    assert_eq!(hex, calculator.calculate_md5_async(&[0x80, 0x01, 0xf0, 0xa5, 0xff, 0x00], 32, None, None, None).await.unwrap());
+}
+
+#[wasm_bindgen_test]
+fn describe_split_matches_the_chunking_policy() {
+    let calculator = Md5Calculator::new();
+    let range = |ranges: &js_sys::Array, i: u32| {
+        let entry = ranges.get(i);
+        [get(&entry, "index"), get(&entry, "start"), get(&entry, "end")].map(|v| v.as_f64().unwrap())
+    };
+
+    let direct: js_sys::Array = calculator.describe_split(1000).into();
+    assert_eq!(direct.length(), 1);
+    assert_eq!(range(&direct, 0), [0.0, 0.0, 1000.0]);
+
+    let chunked: js_sys::Array = calculator.describe_split(600 * 1024).into();
+    assert_eq!(chunked.length(), 5);
+    assert_eq!(range(&chunked, 1), [1.0, 131072.0, 262144.0]);
+    assert_eq!(range(&chunked, 4), [4.0, 524288.0, 614400.0]);
+}
+
+#[wasm_bindgen_test]
+async fn hash_and_put_sends_content_md5_with_the_body() {
+    use wasm_bindgen::JsCast;
+
+    let install = js_sys::Function::new_no_args(
+        "const original = globalThis.fetch;
+        globalThis.fetch = async (request) => {
+            globalThis.fetch = original;
+            const body = new Uint8Array(await request.arrayBuffer());
+            return new Response(JSON.stringify({ method: request.method, md5: request.headers.get('Content-MD5'), body: Array.from(body) }));
+        };",
+    );
+    install.call0(&JsValue::NULL).unwrap();
+
+    let calculator = Md5Calculator::new();
+    let response = calculator.hash_and_put_async("https://example.test/upload", b"abc", JsValue::UNDEFINED).await.unwrap();
+    let text = wasm_bindgen_futures::JsFuture::from(response.text().unwrap()).await.unwrap();
+    let echoed = js_sys::JSON::parse(&text.as_string().unwrap()).unwrap();
+    assert_eq!(get(&echoed, "method").as_string().unwrap(), "PUT");
+    // base64 of the raw digest 900150983cd24fb0d6963f7d28e17f72
+    assert_eq!(get(&echoed, "md5").as_string().unwrap(), "kAFQmDzST7DWlj99KOF/cg==");
+    let body: Vec<f64> = get(&echoed, "body").unchecked_into::<js_sys::Array>().iter().map(|v| v.as_f64().unwrap()).collect();
+    assert_eq!(body, vec![97.0, 98.0, 99.0]);
+}
+
+#[wasm_bindgen_test]
+async fn challenge_digest_hashes_nonce_secret_then_data() {
+    let calculator = Md5Calculator::new();
+    let hex = calculator.calculate_md5_challenge_async(b"n0nce", b"s3cret", b"payload", 32).await.unwrap();
+    assert_eq!(hex, calculator.calculate_md5_async(b"n0nces3cretpayload", 32, None, None, None).await.unwrap());
+    assert_ne!(hex, calculator.calculate_md5_challenge_async(b"s3cret", b"n0nce", b"payload", 32).await.unwrap());
+}
+
+#[wasm_bindgen_test]
+fn session_update_sizes_are_recorded_only_when_tracking() {
+    let mut calculator = Md5Calculator::new();
+    calculator.start_incremental_md5("update-sizes").unwrap();
+    assert!(calculator.update_incremental_md5("update-sizes", b"untracked"));
+    calculator.set_track_update_sizes(true);
+    assert!(calculator.update_incremental_md5("update-sizes", b"abc"));
+    assert!(calculator.update_incremental_md5("update-sizes", b""));
+    assert!(calculator.update_incremental_md5("update-sizes", b"hello"));
+
+    assert_eq!(calculator.session_update_sizes("update-sizes"), Some(vec![3, 0, 5]));
+    assert_eq!(calculator.session_update_sizes("update-sizes-missing"), None);
+    calculator.cancel_incremental_md5("update-sizes");
+}
+
+#[wasm_bindgen_test]
+async fn ssh_string_prefixes_a_big_endian_u32_length() {
+    let calculator = Md5Calculator::new();
+    // MD5 of "\0\0\0\x07ssh-rsa"
+    assert_eq!(calculator.calculate_md5_ssh_string_async(b"ssh-rsa", 32).await.unwrap(), "1507b5d4f7dc4a5814513862c740b755");
+    assert_eq!(calculator.calculate_md5_ssh_string_async(b"", 32).await.unwrap(), calculator.calculate_md5_async(&[0, 0, 0, 0], 32, None, None, None).await.unwrap());
+}
+
+#[wasm_bindgen_test]
+async fn bits_truncation_masks_the_final_partial_byte() {
+    let calculator = Md5Calculator::new();
+    // MD5("abc") = 900150983cd24fb0d6963f7d28e17f72
+    for (bits, expected) in [(1, "80"), (4, "90"), (9, "9000"), (12, "9000"), (40, "900150983c"), (127, "900150983cd24fb0d6963f7d28e17f72")] {
+        assert_eq!(calculator.calculate_md5_bits_async(b"abc", bits).await.unwrap(), expected, "bits = {}", bits);
+    }
+    assert_eq!(calculator.calculate_md5_bits_async(b"abc", 500).await.unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+    assert!(calculator.calculate_md5_bits_async(b"abc", 0).await.is_err());
+}
+
+#[wasm_bindgen_test]
+async fn etag_matching_accepts_weak_and_quoted_forms() {
+    let calculator = Md5Calculator::new();
+    for etag in ["900150983cd24fb0d6963f7d28e17f72", "\"900150983cd24fb0d6963f7d28e17f72\"", "W/\"900150983CD24FB0D6963F7D28E17F72\""] {
+        assert!(calculator.matches_etag_async(b"abc", etag).await, "{}", etag);
+    }
+    assert!(!calculator.matches_etag_async(b"abd", "\"900150983cd24fb0d6963f7d28e17f72\"").await);
+    assert!(!calculator.matches_etag_async(b"abc", "W/\"5f2b-1a8c\"").await);
+}
+
+#[wasm_bindgen_test]
+async fn capped_session_finalizes_once_at_the_limit() {
+    let calculator = Md5Calculator::new();
+    let results = js_sys::Array::new();
+    let on_complete: js_sys::Function = js_sys::Function::new_with_args("results", "return (hex) => results.push(hex);")
+        .call1(&JsValue::NULL, &results)
+        .unwrap()
+        .into();
+
+    calculator.start_incremental_md5_capped("capped", 10, on_complete).unwrap();
+    assert!(calculator.update_incremental_md5("capped", b"0123"));
+    assert!(calculator.update_incremental_md5("capped", b"4567"));
+    assert_eq!(results.length(), 0);
+    assert!(calculator.update_incremental_md5("capped", b"89ab"));
+    assert!(!calculator.update_incremental_md5("capped", b"cdef"));
+
+    assert_eq!(results.length(), 1);
+    assert_eq!(results.get(0).as_string().unwrap(), calculator.calculate_md5_async(b"0123456789", 32, None, None, None).await.unwrap());
+    assert!(calculator.finalize_incremental_md5_strict("capped", 32).is_err());
+}
+
+#[wasm_bindgen_test]
+fn collision_estimate_follows_the_birthday_bound() {
+    let calculator = Md5Calculator::new();
+    assert_eq!(calculator.truncation_collision_estimate(8, 1), 0.0);
+    // 2^16 items over 32 bits: 1 - exp(-(2^16 * (2^16 - 1)) / 2^33) ~= 0.3935
+    assert!((calculator.truncation_collision_estimate(8, 1 << 16) - 0.3935).abs() < 1e-3);
+    assert!(calculator.truncation_collision_estimate(4, 1_000) > 0.99);
+    assert!(calculator.truncation_collision_estimate(32, 1_000_000) < 1e-20);
+}
+
+#[wasm_bindgen_test]
+async fn skip_frames_hashes_only_the_content_runs() {
+    let calculator = Md5Calculator::new();
+    // Content runs of 4 separated by 2-byte headers, ending in a short content run.
+    let framed = b"abcdHHefghHHij";
+    let hex = calculator.calculate_md5_skip_frames_async(framed, 4, 2, 32).await.unwrap();
+    assert_eq!(hex, calculator.calculate_md5_async(b"abcdefghij", 32, None, None, None).await.unwrap());
+    // A short trailing header is skipped.
+    let hex = calculator.calculate_md5_skip_frames_async(b"abcdHHefghH", 4, 2, 32).await.unwrap();
+    assert_eq!(hex, calculator.calculate_md5_async(b"abcdefgh", 32, None, None, None).await.unwrap());
+    assert!(calculator.calculate_md5_skip_frames_async(framed, 0, 2, 32).await.is_err());
+}
+
+#[wasm_bindgen_test]
+async fn combine_sessions_matches_combining_finalized_digests() {
+    let calculator = Md5Calculator::new();
+    for (id, data) in [("combine-a", b"part one"), ("combine-b", b"part two")] {
+        calculator.start_incremental_md5(id).unwrap();
+        assert!(calculator.update_incremental_md5(id, data));
+    }
+
+    let combined = calculator.combine_sessions(vec!["combine-b".into(), "combine-a".into()], 32).unwrap().unwrap();
+    assert_eq!(calculator.combine_sessions(vec!["combine-a".into(), "combine-missing".into()], 32).unwrap(), None);
+
+    let parts = vec![calculator.finalize_incremental_md5("combine-b", 32).unwrap(), calculator.finalize_incremental_md5("combine-a", 32).unwrap()];
+    assert_eq!(combined, calculator.combine_digests(parts, 32).unwrap());
+}
+
+#[wasm_bindgen_test]
+async fn pcm16_digest_is_independent_of_source_byte_order() {
+    let calculator = Md5Calculator::new();
+    let samples: Vec<i16> = (0..300_000).map(|i| (i * 37 % 65_536 - 32_768) as i16).collect();
+    let le: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+    let be: Vec<u8> = samples.iter().flat_map(|s| s.to_be_bytes()).collect();
+
+    let from_le = calculator.calculate_md5_pcm16_async(&le, true, 32).await.unwrap();
+    assert_eq!(from_le, calculator.calculate_md5_pcm16_async(&be, false, 32).await.unwrap());
+    assert_eq!(from_le, calculator.calculate_md5_async(&be, 32, None, None, None).await.unwrap());
+    assert!(calculator.calculate_md5_pcm16_async(&le[..3], true, 32).await.is_err());
+}
+
+#[wasm_bindgen_test]
+fn capabilities_lists_the_compiled_in_algorithms_and_formats() {
+    let caps = wasm_md5::capabilities();
+    let names = |key: &str| -> Vec<String> { js_sys::Array::from(&get(&caps, key)).iter().map(|v| v.as_string().unwrap()).collect() };
+    assert_eq!(names("algorithms").contains(&"sha256".to_string()), cfg!(feature = "sha256"));
+    assert!(names("algorithms").starts_with(&["md5".to_string(), "crc32".to_string()]));
+    assert!(names("formats").starts_with(&["hex".to_string(), "hex_upper".to_string()]));
+    for format in ["base64", "bytes", "etag", "uuid_v3"] {
+        assert!(names("formats").contains(&format.to_string()), "{}", format);
+    }
+    assert_eq!(names("features").contains(&"session-state".to_string()), cfg!(feature = "session-state"));
+    assert_eq!(names("features").contains(&"sha256".to_string()), cfg!(feature = "sha256"));
+}
+
+#[wasm_bindgen_test]
+async fn commit_records_a_save_point_and_keeps_the_session_open() {
+    let calculator = Md5Calculator::new();
+    calculator.start_incremental_md5("commit-doc").unwrap();
+    assert!(calculator.last_commit_incremental_md5("commit-doc", 32).unwrap().is_undefined());
+
+    assert!(calculator.update_incremental_md5("commit-doc", b"draft"));
+    let saved = calculator.commit_incremental_md5("commit-doc", 32).unwrap();
+    assert_eq!(saved, calculator.calculate_md5_async(b"draft", 32, None, None, None).await.unwrap());
+    assert!(calculator.update_incremental_md5("commit-doc", b" edits"));
+
+    let last = calculator.last_commit_incremental_md5("commit-doc", 32).unwrap();
+    assert_eq!(get(&last, "hex").as_string().unwrap(), saved);
+    assert_eq!(get(&last, "bytes").as_f64().unwrap(), 5.0);
+    assert_eq!(calculator.finalize_incremental_md5("commit-doc", 32).unwrap(), calculator.calculate_md5_async(b"draft edits", 32, None, None, None).await.unwrap());
+    assert_eq!(calculator.commit_incremental_md5("commit-doc", 32).unwrap(), "");
+}
+
+#[wasm_bindgen_test]
+async fn rle_digest_matches_the_expanded_buffer() {
+    let calculator = Md5Calculator::new();
+    let runs: [(u8, u32); 4] = [(b'a', 3), (b'b', 0), (0, 200_000), (b'z', 1)];
+    let pairs: Vec<u8> = runs.iter().flat_map(|&(byte, count)| std::iter::once(byte).chain(count.to_le_bytes())).collect();
+    let expanded: Vec<u8> = runs.iter().flat_map(|&(byte, count)| std::iter::repeat_n(byte, count as usize)).collect();
+
+    assert_eq!(calculator.calculate_md5_rle_async(&pairs, 32).await.unwrap(), format!("{:x}", Md5::digest(&expanded)));
+    assert!(calculator.calculate_md5_rle_async(&pairs[..4], 32).await.is_err());
+}
+
+#[wasm_bindgen_test]
+async fn digest_cache_answers_repeats_without_rehashing() {
+    let mut calculator = Md5Calculator::new();
+    calculator.set_cache_capacity(2);
+    let a = patterned_bytes(1000, 1);
+    let mut b = a.clone();
+    b[500] ^= 1; // same length and edges as `a`, so the pre-hash collides
+
+    let hex_a = calculator.calculate_md5_async(&a, 32, None, None, None).await.unwrap();
+    let hashed = calculator.total_bytes_hashed();
+    assert_eq!(calculator.calculate_md5_async(&a, 32, None, None, None).await.unwrap(), hex_a);
+    assert_eq!(calculator.total_bytes_hashed(), hashed);
+
+    assert_eq!(calculator.calculate_md5_async(&b, 32, None, None, None).await.unwrap(), format!("{:x}", Md5::digest(&b)));
+    assert_eq!(calculator.total_bytes_hashed(), hashed + 1000);
+    assert_eq!(calculator.calculate_md5_async(&a, 16, None, None, None).await.unwrap(), hex_a[..16]);
+
+    calculator.set_cache_capacity(0);
+    calculator.calculate_md5_async(&a, 32, None, None, None).await.unwrap();
+    assert_eq!(calculator.total_bytes_hashed(), hashed + 2000);
+}
+
+#[wasm_bindgen_test]
+async fn four_way_split_matches_the_one_shot_digest() {
+    let calculator = Md5Calculator::new();
+    for (seed, len) in [(1, 0), (2, 3), (3, 4096 + 7), (4, 3 * 1024 * 1024 + 5)] {
+        let data = patterned_bytes(len, seed);
+        let session_id = format!("four-way-{}", seed);
+        calculator.start_incremental_md5(&session_id).unwrap();
+        let part = len.div_ceil(4).max(1);
+        for chunk in data.chunks(part) {
+            assert!(calculator.update_incremental_md5(&session_id, chunk));
+        }
+
+        let split = calculator.finalize_incremental_md5(&session_id, 32).unwrap();
+        assert_eq!(split, calculator.calculate_md5_async(&data, 32, None, None, None).await.unwrap(), "len = {}", len);
+        assert_eq!(split, format!("{:x}", Md5::digest(&data)), "len = {}", len);
+    }
+}
+
+#[wasm_bindgen_test]
+async fn progress_is_reported_at_yields_and_once_at_completion() {
+    let calculator = Md5Calculator::new();
+    let data = patterned_bytes(5 * 1024 * 1024 + 17, 9);
+    let seen = js_sys::Array::new();
+    let on_progress: js_sys::Function = js_sys::Function::new_with_args("seen", "return (done, total) => seen.push([done, total]);")
+        .call1(&JsValue::NULL, &seen)
+        .unwrap()
+        .into();
+
+    let hex = calculator.calculate_md5_async(&data, 32, None, Some(on_progress), None).await.unwrap();
+    assert_eq!(hex, format!("{:x}", Md5::digest(&data)));
+
+    let reports: Vec<(f64, f64)> = seen
+        .iter()
+        .map(|pair| {
+            let pair = js_sys::Array::from(&pair);
+            (pair.get(0).as_f64().unwrap(), pair.get(1).as_f64().unwrap())
+        })
+        .collect();
+    let total = data.len() as f64;
+    assert!(reports.iter().all(|&(_, reported_total)| reported_total == total));
+    assert_eq!(reports.iter().filter(|&&(done, _)| done == total).count(), 1);
+    assert_eq!(reports.last().unwrap().0, total);
+    let mut previous = 0.0;
+    for &(done, _) in &reports {
+        assert!(done > previous && done - previous <= (2 * 1024 * 1024) as f64);
+        previous = done;
+    }
+
+    let throwing = js_sys::Function::new_no_args("throw new Error('progress listener failed');");
+    assert_eq!(calculator.calculate_md5_async(&data, 32, None, Some(throwing), None).await.unwrap(), hex);
+}
+
+#[wasm_bindgen_test]
+fn instance_stream_digests_and_restarts() {
+    let mut calculator = Md5Calculator::new();
+    calculator.update(b"ab");
+    calculator.update(b"c");
+    assert_eq!(calculator.digest(32).unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+    assert_eq!(calculator.digest(32).unwrap(), format!("{:x}", Md5::digest(b"")));
+
+    calculator.update(b"discarded");
+    calculator.reset();
+    calculator.update(b"abc");
+    assert_eq!(calculator.digest(16).unwrap(), "900150983cd24fb0");
+}
+
+#[wasm_bindgen_test]
+fn sessions_can_be_listed_and_cleared() {
+    let calculator = Md5Calculator::new();
+    assert!(!calculator.start_incremental_md5("audit-b").unwrap());
+    assert!(!calculator.start_incremental_md5("audit-a").unwrap());
+    assert!(calculator.start_incremental_md5("audit-a").unwrap());
+
+    let audited: Vec<String> = calculator.list_active_sessions().into_iter().filter(|id| id.starts_with("audit-")).collect();
+    assert_eq!(audited, ["audit-a", "audit-b"]);
+
+    assert!(calculator.clear_all_sessions() >= 2);
+    assert!(calculator.list_active_sessions().is_empty());
+    assert!(!calculator.update_incremental_md5("audit-a", b"gone"));
+
+    // Clearing leaves no stale ownership behind: a session started after the clear survives
+    // dropping the instance that held the id before it.
+    let holder = Md5Calculator::new();
+    holder.start_incremental_md5("cleared-stale").unwrap();
+    assert!(calculator.clear_all_sessions() >= 1);
+    let other = Md5Calculator::new();
+    other.start_incremental_md5("cleared-stale").unwrap();
+    drop(holder);
+    assert!(other.update_incremental_md5("cleared-stale", b"kept"));
+    other.cancel_incremental_md5("cleared-stale");
+}
+
+#[wasm_bindgen_test]
+async fn detailed_result_reports_lengths_and_duration() {
+    let calculator = Md5Calculator::new();
+    let result = calculator.calculate_md5_detailed_async(b"", 16).await.unwrap();
+    assert_eq!(get(&result, "hex").as_string().unwrap(), "d41d8cd98f00b204");
+    assert_eq!(get(&result, "byteLength").as_f64().unwrap(), 0.0);
+    assert_eq!(get(&result, "truncatedLength").as_f64().unwrap(), 16.0);
+    assert!(get(&result, "durationMs").as_f64().unwrap() >= 0.0);
+}
+
+#[wasm_bindgen_test]
+async fn verify_md5_accepts_16_and_32_char_digests_only() {
+    let calculator = Md5Calculator::new();
+    assert!(calculator.verify_md5_async(b"abc", "900150983cd24fb0d6963f7d28e17f72").await);
+    assert!(calculator.verify_md5_async(b"abc", "900150983CD24FB0").await);
+    assert!(!calculator.verify_md5_async(b"abd", "900150983cd24fb0d6963f7d28e17f72").await);
+    assert!(!calculator.verify_md5_async(b"abc", "900150983cd24fb0d696").await);
+    assert!(!calculator.verify_md5_async(b"abc", "900150983cd24fbz").await);
+    assert!(!calculator.verify_md5_async(b"abc", "").await);
+}
+
+#[wasm_bindgen_test]
+async fn blobs_and_streams_hash_like_the_buffer() {
+    let calculator = Md5Calculator::new();
+    let data = patterned_bytes(3 * 1024 * 1024 + 11, 12);
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(&data[..]));
+    let blob = web_sys::Blob::new_with_u8_array_sequence(&parts).unwrap();
+
+    let expected = format!("{:x}", Md5::digest(&data));
+    assert_eq!(calculator.calculate_md5_blob_async(blob.clone(), 32).await.unwrap(), expected);
+    assert_eq!(calculator.calculate_md5_stream_async(blob.stream(), 16).await.unwrap(), expected[..16]);
+}
+
+#[wasm_bindgen_test]
+async fn abort_signal_stops_hashing_at_the_next_yield() {
+    use wasm_bindgen::JsCast;
+
+    let calculator = Md5Calculator::new();
+    let data = patterned_bytes(8 * 1024 * 1024, 13);
+    let wiring = js_sys::Function::new_no_args(
+        "const controller = new AbortController(); const seen = [];
+        return { signal: controller.signal, seen, onProgress: (done) => { seen.push(done); controller.abort(); } };",
+    )
+    .call0(&JsValue::NULL)
+    .unwrap();
+    let signal: web_sys::AbortSignal = get(&wiring, "signal").unchecked_into();
+    let on_progress: js_sys::Function = get(&wiring, "onProgress").unchecked_into();
+
+    assert_eq!(calculator.calculate_md5_async(&data, 32, None, Some(on_progress), Some(signal.clone())).await.unwrap(), "");
+    assert_eq!(js_sys::Array::from(&get(&wiring, "seen")).length(), 1);
+    assert_eq!(calculator.calculate_md5_async(b"abc", 32, None, None, Some(signal)).await.unwrap(), "");
+    assert_eq!(calculator.calculate_md5_async(&data, 32, None, None, None).await.unwrap(), format!("{:x}", Md5::digest(&data)));
+}
+
+#[wasm_bindgen_test]
+async fn md5_length_outside_1_to_32_rejects() {
+    let calculator = Md5Calculator::new();
+    assert_eq!(calculator.calculate_md5_async(b"abc", 16, None, None, None).await.unwrap(), "900150983cd24fb0");
+    assert_eq!(calculator.calculate_md5_async(b"abc", 32, None, None, None).await.unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+    for invalid in [0, 33, 64] {
+        let error = js_sys::Error::from(calculator.calculate_md5_async(b"abc", invalid, None, None, None).await.unwrap_err());
+        assert_eq!(error.message(), format!("md5_length must be between 1 and 32, got {}", invalid));
+        assert!(calculator.calculate_md5_checked_async(b"abc", invalid).await.is_err());
+    }
+}
+
+#[cfg(feature = "sha256")]
+#[wasm_bindgen_test]
+async fn sha256_one_shot_and_incremental_agree() {
+    let calculator = Md5Calculator::new();
+    let expected = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+    assert_eq!(calculator.calculate_sha256_async(b"abc").await, expected);
+
+    assert!(!calculator.start_incremental_sha256("sha-abc").unwrap());
+    assert!(calculator.update_incremental_sha256("sha-abc", b"a"));
+    assert!(calculator.update_incremental_sha256("sha-abc", b"bc"));
+    assert!(!calculator.update_incremental_sha256("sha-missing", b"x"));
+    assert_eq!(calculator.finalize_incremental_sha256("sha-abc"), expected);
+    assert_eq!(calculator.finalize_incremental_sha256("sha-abc"), "");
+}
+
+#[wasm_bindgen_test]
+async fn batch_hashes_every_buffer_in_order() {
+    let calculator = Md5Calculator::new();
+    let inputs: [&[u8]; 3] = [b"abc", b"", b"hello"];
+    let files: Vec<js_sys::Uint8Array> = inputs.iter().map(|&bytes| js_sys::Uint8Array::from(bytes)).collect();
+
+    let hexes = calculator.calculate_md5_batch_async(files, 32).await.unwrap();
+    let expected: Vec<String> = inputs.iter().map(|bytes| format!("{:x}", Md5::digest(bytes))).collect();
+    assert_eq!(hexes, expected);
+    assert!(calculator.calculate_md5_batch_async(Vec::new(), 16).await.unwrap().is_empty());
+    assert!(calculator.calculate_md5_batch_async(Vec::new(), 0).await.is_err());
+}
+
+#[wasm_bindgen_test]
+async fn regions_hash_independently_and_combine_their_digests() {
+    let calculator = Md5Calculator::new();
+    let result = calculator.calculate_md5_regions_async(b"abcdefg", 3, 32).await.unwrap();
+    let regions: js_sys::Array = get(&result, "regions").into();
+    assert_eq!(regions.length(), 3);
+
+    let mut raw = Vec::new();
+    for (i, expected) in [&b"abc"[..], b"de", b"fg"].iter().enumerate() {
+        let region = regions.get(i as u32);
+        let hex = calculator.calculate_md5_async(expected, 32, None, None, None).await.unwrap();
+        assert_eq!(get(&region, "hex").as_string().unwrap(), hex);
+        raw.extend(hex_to_bytes(&hex).unwrap());
+    }
+    let combined = calculator.calculate_md5_async(&raw, 32, None, None, None).await.unwrap();
+    assert_eq!(get(&result, "combined").as_string().unwrap(), combined);
+    assert!(calculator.calculate_md5_regions_async(b"abc", 0, 32).await.is_err());
+}
+
+#[wasm_bindgen_test]
+fn peek_returns_the_partial_digest_without_finalizing() {
+    let calculator = Md5Calculator::new();
+    calculator.start_incremental_md5("peek").unwrap();
+    calculator.update_incremental_md5("peek", b"abc");
+    assert_eq!(calculator.peek_incremental_md5("peek", 32).unwrap(), format!("{:x}", Md5::digest(b"abc")));
+    assert_eq!(calculator.peek_incremental_md5("peek", 16).unwrap().len(), 16);
+
+    calculator.update_incremental_md5("peek", b"def");
+    assert_eq!(calculator.finalize_incremental_md5("peek", 32).unwrap(), format!("{:x}", Md5::digest(b"abcdef")));
+    assert_eq!(calculator.peek_incremental_md5("peek", 32).unwrap(), "");
+}
+
+#[wasm_bindgen_test]
+async fn last_stats_describe_the_latest_call() {
+    let calculator = Md5Calculator::new();
+    let stats = calculator.last_stats();
+    assert_eq!(get(&stats, "bytesHashed").as_f64(), Some(0.0));
+
+    let data = vec![7u8; 3 * 1024 * 1024];
+    calculator.calculate_md5_async(&data, 32, None, None, None).await.unwrap();
+    let stats = calculator.last_stats();
+    assert_eq!(get(&stats, "bytesHashed").as_f64(), Some(data.len() as f64));
+    assert!(get(&stats, "elapsedMs").as_f64().unwrap() >= 0.0);
+
+    calculator.calculate_md5_async(&[], 32, None, None, None).await.unwrap();
+    let stats = calculator.last_stats();
+    assert_eq!(get(&stats, "bytesHashed").as_f64(), Some(0.0));
+    assert_eq!(get(&stats, "mbPerSecond").as_f64(), Some(0.0));
+}
+
+#[wasm_bindgen_test]
+async fn range_hashes_only_the_requested_bytes() {
+    let calculator = Md5Calculator::new();
+    let data = b"xxabcdefyy";
+    assert_eq!(calculator.calculate_md5_range_async(data, 2, 6, 32).await.unwrap(), format!("{:x}", Md5::digest(b"abcdef")));
+    assert_eq!(calculator.calculate_md5_range_async(data, 10, 0, 32).await.unwrap(), format!("{:x}", Md5::digest(b"")));
+    assert!(calculator.calculate_md5_range_async(data, 8, 3, 32).await.is_err());
+    assert!(calculator.calculate_md5_range_async(data, usize::MAX, 2, 32).await.is_err());
+}
+
+#[wasm_bindgen_test]
+fn dropping_a_calculator_drops_the_sessions_it_started() {
+    let survivor = Md5Calculator::new();
+    {
+        let scoped = Md5Calculator::new();
+        scoped.start_incremental_md5("drop-owned").unwrap();
+        scoped.start_incremental_md5("drop-restarted").unwrap();
+        scoped.start_rolling_md5("drop-rolling", 4).unwrap();
+        assert!(survivor.start_incremental_md5("drop-restarted").unwrap());
+    }
+    assert!(!survivor.update_incremental_md5("drop-owned", b"abc"));
+    assert!(!survivor.push_rolling_md5("drop-rolling", b"abc"));
+    assert!(survivor.update_incremental_md5("drop-restarted", b"abc"));
+    assert_eq!(survivor.finalize_incremental_md5("drop-restarted", 32).unwrap(), format!("{:x}", Md5::digest(b"abc")));
+}
+
+#[wasm_bindgen_test]
+async fn crc32_matches_the_combined_md5_crc_result() {
+    let calculator = Md5Calculator::new();
+    assert_eq!(calculator.calculate_crc32_async(b"123456789").await, 0xcbf4_3926);
+    assert_eq!(calculator.calculate_crc32_async(b"").await, 0);
+
+    let data = vec![0x5au8; 700 * 1024];
+    let combined = calculator.calculate_md5_crc_async(&data, 32).await.unwrap();
+    assert_eq!(get(&combined, "crc32").as_f64(), Some(f64::from(calculator.calculate_crc32_async(&data).await)));
+}
+
+#[wasm_bindgen_test]
+async fn raw_digest_bytes_match_the_hex_digest() {
+    let calculator = Md5Calculator::new();
+    let expected = Md5::digest(b"abc").to_vec();
+    assert_eq!(calculator.calculate_md5_bytes_async(b"abc").await, expected);
+
+    let seen = js_sys::Array::new();
+    let on_finalize: js_sys::Function = js_sys::Function::new_with_args("seen", "return (id, hex) => seen.push(hex);")
+        .call1(&JsValue::NULL, &seen)
+        .unwrap()
+        .into();
+    calculator.set_on_finalize(on_finalize);
+
+    calculator.start_incremental_md5("bytes").unwrap();
+    calculator.update_incremental_md5("bytes", b"ab");
+    calculator.update_incremental_md5("bytes", b"c");
+    assert_eq!(calculator.finalize_incremental_md5_bytes("bytes"), expected);
+    assert!(calculator.finalize_incremental_md5_bytes("bytes").is_empty());
+    assert_eq!(seen.length(), 1);
+    assert_eq!(seen.get(0).as_string().unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+
+    calculator.start_incremental_md5("bytes-gap").unwrap();
+    assert!(calculator.update_incremental_md5_at("bytes-gap", 1, b"c"));
+    assert!(calculator.finalize_incremental_md5_bytes("bytes-gap").is_empty());
+    assert!(calculator.update_incremental_md5_at("bytes-gap", 0, b"ab"));
+    assert_eq!(calculator.finalize_incremental_md5_bytes("bytes-gap"), expected);
+}
+
+#[wasm_bindgen_test]
+async fn single_pass_matches_the_chunked_path() {
+    let calculator = Md5Calculator::new();
+    let data = vec![0x3cu8; 3 * 1024 * 1024 + 5];
+    let chunked = calculator.calculate_md5_async(&data, 16, None, None, None).await.unwrap();
+    assert_eq!(calculator.calculate_md5_single_async(&data, 16).await.unwrap(), chunked);
+    assert!(calculator.calculate_md5_single_async(&data, 0).await.is_err());
+}
+
+#[wasm_bindgen_test]
+async fn empty_input_hashes_to_the_md5_of_zero_bytes() {
+    const EMPTY_MD5: &str = "d41d8cd98f00b204e9800998ecf8427e";
+    let calculator = Md5Calculator::new();
+    assert_eq!(calculator.calculate_md5_async(&[], 32, None, None, None).await.unwrap(), EMPTY_MD5);
+    assert_eq!(calculator.calculate_md5_async(&[], 16, None, None, None).await.unwrap(), &EMPTY_MD5[..16]);
+    assert_eq!(calculator.calculate_md5_single_async(&[], 32).await.unwrap(), EMPTY_MD5);
+    assert_eq!(calculator.calculate_md5_batch_async(vec![js_sys::Uint8Array::new_with_length(0)], 32).await.unwrap(), [EMPTY_MD5]);
+
+    calculator.start_incremental_md5("empty").unwrap();
+    assert_eq!(calculator.finalize_incremental_md5("empty", 32).unwrap(), EMPTY_MD5);
+    assert_eq!(Md5Stream::new().finalize(32).unwrap(), EMPTY_MD5);
+}
+
+#[wasm_bindgen_test]
+async fn array_buffer_input_matches_the_byte_slice_path() {
+    let calculator = Md5Calculator::new();
+    let buffer = js_sys::Uint8Array::from(&b"hello world"[..]).buffer();
+    assert_eq!(
+        calculator.calculate_md5_array_buffer_async(buffer, 32).await.unwrap(),
+        calculator.calculate_md5_async(b"hello world", 32, None, None, None).await.unwrap()
+    );
+    assert!(calculator.calculate_md5_array_buffer_async(js_sys::ArrayBuffer::new(0), 40).await.is_err());
+}
+
+#[wasm_bindgen_test]
+fn incremental_methods_reject_out_of_range_lengths() {
+    let calculator = Md5Calculator::new();
+    calculator.start_incremental_md5("length-checked").unwrap();
+    assert!(calculator.update_incremental_md5("length-checked", b"abc"));
+
+    for &length in &[0, 33] {
+        assert!(calculator.peek_incremental_md5("length-checked", length).is_err());
+        assert!(calculator.finalize_incremental_md5("length-checked", length).is_err());
+    }
+    // The rejected finalize calls left the session alive.
+    assert_eq!(calculator.finalize_incremental_md5("length-checked", 32).unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+}
+
+#[wasm_bindgen_test]
+fn session_limit_rejects_new_ids_until_one_is_finalized() {
+    let mut calculator = Md5Calculator::new();
+    assert!(calculator.set_max_sessions(0).is_err());
+    let live = calculator.session_count();
+    calculator.set_max_sessions(live + 2).unwrap();
+    let limited = |result: Result<(), JsValue>| get(&result.unwrap_err(), "code").as_string().unwrap() == "session_limit";
+
+    calculator.start_incremental_md5("limit-a").unwrap();
+    calculator.start_incremental_md5("limit-b").unwrap();
+    assert_eq!(calculator.session_count(), live + 2);
+    assert!(limited(calculator.start_incremental_md5("limit-c").map(drop)));
+    assert!(limited(calculator.start_incremental_md5_salted("limit-c", b"salt")));
+    assert!(limited(calculator.start_incremental_md5_domain("limit-c", "domain")));
+    assert!(limited(calculator.start_incremental_md5_from("limit-c", b"seed")));
+    assert!(limited(calculator.start_incremental_md5_with_meta("limit-c", JsValue::NULL)));
+    assert!(limited(calculator.start_incremental_md5_capped("limit-c", 4, js_sys::Function::new_no_args(""))));
+    #[cfg(feature = "session-state")]
+    {
+        let state = calculator.export_incremental_md5("limit-a").unwrap();
+        assert!(limited(calculator.import_incremental_md5("limit-c", &state).map(drop)));
+    }
+    assert_eq!(calculator.session_count(), live + 2);
+    assert!(!calculator.update_incremental_md5("limit-c", b"abc"));
+
+    assert!(calculator.start_incremental_md5("limit-a").unwrap());
+    assert!(calculator.update_incremental_md5("limit-a", b"abc"));
+    assert_eq!(calculator.finalize_incremental_md5("limit-a", 32).unwrap(), format!("{:x}", Md5::digest(b"abc")));
+    assert!(!calculator.start_incremental_md5("limit-c").unwrap());
+    assert_eq!(calculator.session_count(), live + 2);
+    calculator.cancel_incremental_md5("limit-b");
+    calculator.cancel_incremental_md5("limit-c");
+
+    // Rolling and SHA-256 sessions count against the same limit in their own maps.
+    let rolling: Vec<String> = (0..=live + 2).map(|i| format!("limit-rolling-{}", i)).collect();
+    let refused = rolling.iter().position(|id| calculator.start_rolling_md5(id, 4).is_err()).unwrap();
+    assert!(limited(calculator.start_rolling_md5(&rolling[refused], 4).map(drop)));
+    for id in &rolling[..refused] {
+        assert!(calculator.cancel_rolling_md5(id));
+    }
+    #[cfg(feature = "sha256")]
+    {
+        let sha: Vec<String> = (0..=live + 2).map(|i| format!("limit-sha-{}", i)).collect();
+        let refused = sha.iter().position(|id| calculator.start_incremental_sha256(id).is_err()).unwrap();
+        assert!(limited(calculator.start_incremental_sha256(&sha[refused]).map(drop)));
+        for id in &sha[..refused] {
+            assert!(calculator.cancel_incremental_sha256(id));
+        }
+    }
+}
+
+#[wasm_bindgen_test]
+async fn double_md5_hashes_the_inner_hex_string() {
+    let calculator = Md5Calculator::new();
+    assert_eq!(calculator.calculate_md5_double_async(b"abc", 32).await.unwrap(), "ec0405c5aef93e771cd80e0db180b88b");
+    assert_eq!(calculator.calculate_md5_double_async(b"abc", 16).await.unwrap(), "ec0405c5aef93e77");
+}
+
+#[wasm_bindgen_test]
+async fn rejected_calls_leave_the_instance_usable() {
+    let calculator = Md5Calculator::new();
+    assert!(calculator.calculate_md5_async(b"abc", 0, None, None, None).await.is_err());
+    assert!(calculator.calculate_md5_range_async(b"abc", 2, 5, 32).await.is_err());
+    assert!(hex_to_bytes("zz").is_err());
+    assert_eq!(calculator.calculate_md5_async(b"abc", 32, None, None, None).await.unwrap(), format!("{:x}", Md5::digest(b"abc")));
+}
+
+#[wasm_bindgen_test]
+fn indexed_chunks_are_applied_in_order_and_gaps_block_finalize() {
+    let calculator = Md5Calculator::new();
+    calculator.start_incremental_md5("at").unwrap();
+    assert!(calculator.update_incremental_md5_at("at", 2, b"ef"));
+    assert!(calculator.update_incremental_md5_at("at", 0, b"ab"));
+    assert!(!calculator.update_incremental_md5_at("at", 0, b"ab"));
+    assert!(!calculator.update_incremental_md5_at("at", 2, b"ef"));
+
+    assert_eq!(calculator.finalize_incremental_md5("at", 32).unwrap(), "");
+    let error = calculator.finalize_incremental_md5_strict("at", 32).unwrap_err();
+    assert_eq!(get(&error, "code").as_string().unwrap(), "chunk_gap");
+    assert_eq!(calculator.finalize_and_restart_incremental("at", 32).unwrap(), None);
+
+    assert!(calculator.update_incremental_md5_at("at", 1, b"cd"));
+    assert_eq!(calculator.finalize_incremental_md5("at", 32).unwrap(), format!("{:x}", Md5::digest(b"abcdef")));
+    assert!(!calculator.update_incremental_md5_at("missing", 0, b"ab"));
+}
+
+#[wasm_bindgen_test]
+async fn self_test_passes_without_counting_bytes() {
+    let calculator = Md5Calculator::new();
+    assert!(calculator.self_test_async().await);
+    assert_eq!(calculator.total_bytes_hashed(), 0);
+}
+
+#[wasm_bindgen_test]
+async fn region_sizes_differ_by_at_most_one_byte() {
+    let calculator = Md5Calculator::new();
+    let data = vec![1u8; 9 * 1024 * 1024 + 5];
+    for &count in &[1, 3, 8, 7] {
+        let result = calculator.calculate_md5_regions_async(&data, count, 32).await.unwrap();
+        let regions: js_sys::Array = get(&result, "regions").into();
+        let sizes: Vec<f64> = regions.iter().map(|region| get(&region, "end").as_f64().unwrap() - get(&region, "start").as_f64().unwrap()).collect();
+        let (min, max) = sizes.iter().fold((f64::MAX, 0.0f64), |(min, max), &size| (min.min(size), max.max(size)));
+        assert!(max - min <= 1.0, "count {}: {:?}", count, sizes);
+        assert_eq!(sizes.iter().sum::<f64>(), data.len() as f64);
+    }
+}
+
+#[wasm_bindgen_test]
+fn update_and_peek_returns_the_running_digest() {
+    let calculator = Md5Calculator::new();
+    calculator.start_incremental_md5("live").unwrap();
+    assert_eq!(calculator.update_and_peek_incremental_md5("live", b"ab", 32).unwrap(), format!("{:x}", Md5::digest(b"ab")));
+    assert_eq!(calculator.update_and_peek_incremental_md5("live", b"c", 16).unwrap(), &format!("{:x}", Md5::digest(b"abc"))[..16]);
+    assert_eq!(calculator.finalize_incremental_md5("live", 32).unwrap(), format!("{:x}", Md5::digest(b"abc")));
+    assert_eq!(calculator.update_and_peek_incremental_md5("live", b"d", 32).unwrap(), "");
+}
+
+#[wasm_bindgen_test]
+async fn options_object_configures_the_calculator() {
+    let parse = |json: &str| js_sys::JSON::parse(json).unwrap();
+    let defaults = Md5Calculator::with_options(JsValue::UNDEFINED).unwrap();
+    assert_eq!(defaults.calculate_md5_async(b"abc", 32, None, None, None).await.unwrap(), format!("{:x}", Md5::digest(b"abc")));
+    assert!(!Md5Calculator::with_options(parse("{}")).unwrap().is_log_enabled());
+
+    let calculator = Md5Calculator::with_options(parse(r#"{"enableLog": true, "chunkSize": 65536, "taskCount": 0}"#)).unwrap();
+    assert!(calculator.is_log_enabled());
+    assert_eq!(get(&calculator.plan_md5(1024 * 1024), "chunk_size").as_f64(), Some(65536.0));
+    assert_eq!(calculator.calculate_md5_async(b"abc", 16, None, None, None).await.unwrap(), "900150983cd24fb0");
+
+    assert!(Md5Calculator::with_options(parse(r#"{"chunkSize": 0}"#)).is_err());
+    assert!(Md5Calculator::with_options(parse(r#"{"enableLog": "yes"}"#)).is_err());
+}
+
+#[wasm_bindgen_test]
+async fn js_array_input_hashes_like_calculate_md5_async() {
+    use wasm_bindgen::JsCast;
+
+    let calculator = Md5Calculator::new();
+    let small = js_sys::Uint8Array::from(&b"abc"[..]);
+    assert_eq!(calculator.calculate_md5_from_js_async(small, 32, None, None).await.unwrap(), format!("{:x}", Md5::digest(b"abc")));
+    assert_eq!(get(&calculator.last_stats(), "bytesHashed").as_f64(), Some(3.0));
+    assert!(calculator.last_hash_peak_memory_bytes() > 0);
+    let empty = js_sys::Uint8Array::new_with_length(0);
+    assert_eq!(calculator.calculate_md5_from_js_async(empty, 32, None, None).await.unwrap(), format!("{:x}", Md5::digest(b"")));
+
+    let data = patterned_bytes(3 * 1024 * 1024 + 5, 9);
+    let seen = js_sys::Array::new();
+    let on_progress: js_sys::Function = js_sys::Function::new_with_args("seen", "return (processed, total) => seen.push(processed);")
+        .call1(&JsValue::NULL, &seen)
+        .unwrap()
+        .into();
+    let hex = calculator.calculate_md5_from_js_async(js_sys::Uint8Array::from(&data[..]), 32, Some(on_progress), None).await.unwrap();
+    assert_eq!(hex, format!("{:x}", Md5::digest(&data)));
+    assert!(seen.length() >= 2);
+    assert_eq!(seen.get(seen.length() - 1).as_f64(), Some(data.len() as f64));
+
+    let signal: web_sys::AbortSignal = js_sys::Function::new_no_args("return AbortSignal.abort();").call0(&JsValue::NULL).unwrap().unchecked_into();
+    let aborted = calculator.calculate_md5_from_js_async(js_sys::Uint8Array::from(&data[..]), 32, None, Some(signal)).await;
+    assert_eq!(aborted.unwrap(), "");
+}